@@ -0,0 +1,129 @@
+// Service worker that lets birdtalk be played in the field without a connection.
+//
+// - Pages and app assets are served from the network when possible, falling back to the cache.
+// - Database reads (e.g. upcoming daily packs) are cached as they're fetched, so the last known
+//   response is served when offline.
+// - Bird images and sounds (from storage, or a CDN serving the same paths) are cached the first
+//   time they're fetched, or ahead of time when the app posts a
+//   `{ type: "cache-media", urls: [...] }` message.
+
+const VERSION = "v2";
+const SHELL_CACHE = `shell-${VERSION}`;
+const DATA_CACHE = `data-${VERSION}`;
+const MEDIA_CACHE = `media-${VERSION}`;
+const CACHES = [SHELL_CACHE, DATA_CACHE, MEDIA_CACHE];
+
+const SHELL = ["/", "/manifest.webmanifest"];
+
+// public, transformed and signed storage objects
+const MEDIA_PATH = /\/(object|render\/image)\/(public|sign)\//;
+// Bird media is signed again whenever it's fetched, with a new token each time, so signed objects
+// are cached without their token (but with any image transformation, which the app adds to the
+// URL). The app falls back to such URLs when it can't sign media, e.g. offline.
+const SIGNED_PATH = /\/(object|render\/image)\/sign\//;
+
+function cacheKey(url) {
+  const key = new URL(url);
+  if (SIGNED_PATH.test(key.pathname)) {
+    key.searchParams.delete("token");
+  }
+  return key.href;
+}
+
+self.addEventListener("install", (event) => {
+  event.waitUntil(
+    caches
+      .open(SHELL_CACHE)
+      .then((cache) => cache.addAll(SHELL))
+      .then(() => self.skipWaiting()),
+  );
+});
+
+self.addEventListener("activate", (event) => {
+  event.waitUntil(
+    caches
+      .keys()
+      .then((keys) =>
+        Promise.all(
+          keys.filter((key) => !CACHES.includes(key)).map((key) => caches.delete(key)),
+        ),
+      )
+      .then(() => self.clients.claim()),
+  );
+});
+
+self.addEventListener("message", (event) => {
+  const message = event.data || {};
+  if (message.type === "cache-media" && Array.isArray(message.urls)) {
+    event.waitUntil(cacheMedia(message.urls));
+  }
+});
+
+self.addEventListener("fetch", (event) => {
+  const request = event.request;
+  if (request.method !== "GET") {
+    return;
+  }
+  const url = new URL(request.url);
+  if (MEDIA_PATH.test(url.pathname)) {
+    event.respondWith(cacheFirst(MEDIA_CACHE, request));
+  } else if (url.pathname.includes("/rest/v1/")) {
+    event.respondWith(networkFirst(DATA_CACHE, request));
+  } else if (request.mode === "navigate") {
+    // the app routes on the client, so any cached page will do
+    event.respondWith(networkFirst(SHELL_CACHE, request, "/"));
+  } else if (url.origin === self.location.origin) {
+    event.respondWith(networkFirst(SHELL_CACHE, request));
+  }
+});
+
+async function cacheMedia(urls) {
+  const cache = await caches.open(MEDIA_CACHE);
+  await Promise.all(
+    urls.map(async (url) => {
+      if (await cache.match(cacheKey(url))) {
+        return;
+      }
+      try {
+        const response = await fetch(url);
+        if (response.ok) {
+          await cache.put(cacheKey(url), response);
+        }
+      } catch (e) {
+        console.warn(`Failed to cache ${url}`, e);
+      }
+    }),
+  );
+}
+
+async function cacheFirst(cacheName, request) {
+  const cache = await caches.open(cacheName);
+  const cached = await cache.match(cacheKey(request.url));
+  if (cached) {
+    return cached;
+  }
+  const response = await fetch(request);
+  // partial (range) responses can't be cached
+  if (response.status === 200) {
+    cache.put(cacheKey(request.url), response.clone());
+  }
+  return response;
+}
+
+async function networkFirst(cacheName, request, fallbackUrl) {
+  const cache = await caches.open(cacheName);
+  try {
+    const response = await fetch(request);
+    if (response.status === 200) {
+      cache.put(request, response.clone());
+    }
+    return response;
+  } catch (e) {
+    const cached =
+      (await cache.match(request)) || (fallbackUrl && (await cache.match(fallbackUrl)));
+    if (cached) {
+      return cached;
+    }
+    throw e;
+  }
+}
@@ -0,0 +1,170 @@
+## Layout
+copyright = © 2024 birdtalk
+github-repository = GitHub Repository
+your-aviary = Your Aviary
+donate = Donate
+language = Language
+close-modal = Close Modal
+
+## Landing page
+tagline-daily = 10 new birds every day
+tagline-game = A game that helps you memorize bird calls.
+wild-speaks = The wild speaks.
+wild-speaks-body = One of the best ways to spot a bird is to hear it first. Learn to recognize new calls here for your next adventure out in the field.
+
+## Errors
+uh-oh = Uh oh! 😱
+github-issue = GitHub issue
+report-error = Please open a { $link } with the following error:
+error-fetching-daily-pack = Something went wrong fetching today's challenge.
+error-fetching-birds = Something went wrong fetching your birds!
+error-no-daily-pack = Uh oh! We couldn't find today's pack!
+error-no-pack = No pack found with id { $id } 🙈
+error-birds-not-learned = You haven't learned some of these birds yet!
+error-network = We couldn't reach the server. Please check your connection and try again.
+error-unauthorized = You're not allowed to do that. Try signing in again.
+error-server = Something went wrong on our end. Please try again later.
+retry = Try again
+error-invalid-config = birdtalk is misconfigured, so it can't load any birds.
+
+## Pack of the day
+next-bird = Next Bird
+play = play
+pause = Pause
+play-audio = Play
+audio-not-supported = Your browser does not support the audio element.
+pack-of-the-day = Pack of the Day
+
+## Packs
+packs = Packs
+browse-packs = Browse more packs
+pack-bird-count = { $count ->
+    [one] 1 bird
+   *[other] { $count } birds
+}
+locked = Locked
+premium-pitch = Unlock every pack to learn even more birds, and support birdtalk!
+unlock-all-packs = Unlock all packs
+unlock-pack = Unlock pack
+pack-locked-title = This pack is locked 🔒
+pack-locked = Unlock every pack to play it.
+sign-in-to-unlock = Sign in first, so your packs stay unlocked on all your devices.
+error-fetching-packs = Something went wrong fetching packs!
+error-checkout = We couldn't start the checkout. Please try again later.
+
+## Aviary
+empty-nest = An empty nest 🪹
+empty-nest-body = You need to learn more birds to fill out your aviary!
+play-to-get-started = Play the { $link } to get started!
+aviary-intro = Here are all the birds you've learned so far! 🐦 Continue to play the { $link } to learn more!
+aviary-review-locked = Once you've learned { $count } birds, come back here to play a round of review!
+select-birds-to-review = Select { $count } birds to review
+select-more-birds = { $count ->
+    [one] Select 1 more bird
+   *[other] Select { $count } more birds
+}
+review = review
+average-recognition-time = Recognized in { $seconds } s on average
+most-confused-pairs = Most confused pairs
+times-confused = { $count ->
+    [one] 1 time
+   *[other] { $count } times
+}
+review-confused = Review these birds
+
+## Progress
+your-progress = Your Progress
+see-your-progress = See your progress over time
+no-progress-yet = Play a game and your progress will show up here!
+chart-birds-learned = Birds learned: { $count }
+chart-accuracy = Accuracy: { $percent }%
+daily-pack-calendar = { $count ->
+    [one] 1 daily pack completed
+   *[other] { $count } daily packs completed
+}
+calendar-played = Played
+calendar-daily-pack = Daily pack completed
+calendar-streak-freeze = Streak freeze used
+streak-freezes = { $count ->
+    [one] 1 streak freeze held
+   *[other] { $count } streak freezes held
+}. Earn one every { $days } days in a row, and keep your streak when you miss a day.
+streak-time-zone = Days are counted in
+use-device-time-zone = Use this device's time zone ({ $zone })
+family-mastery = Birds learned per family
+unknown-family = Other birds
+
+## Trophies
+see-your-trophies = See your trophies
+trophies = Trophies
+trophies-unlocked = { $count } of { $total } unlocked
+unlocked-on = Unlocked on { $date }
+achievements-unlocked = { $count ->
+    [one] Achievement unlocked!
+   *[other] { $count } achievements unlocked!
+}
+achievement-first-bird = First Feather
+achievement-first-bird-description = Learn your first bird
+achievement-flock = Flock Together
+achievement-flock-description = Learn 25 birds
+achievement-aviary = Full Aviary
+achievement-aviary-description = Learn 100 birds
+achievement-daily-streak-3 = Early Bird
+achievement-daily-streak-3-description = Complete the daily pack 3 days in a row
+achievement-daily-streak-7 = Creature of Habit
+achievement-daily-streak-7-description = Complete the daily pack 7 days in a row
+achievement-daily-streak-30 = Migration
+achievement-daily-streak-30-description = Complete the daily pack 30 days in a row
+achievement-streak-10 = Sharp Ears
+achievement-streak-10-description = Identify 10 birds in a row
+achievement-streak-25 = Perfect Pitch
+achievement-streak-25-description = Identify 25 birds in a row
+achievement-owls = Night Owl
+achievement-owls-description = Learn every owl
+achievement-woodpeckers = Knock Knock
+achievement-woodpeckers-description = Learn every woodpecker
+achievement-raptors = Birds of Prey
+achievement-raptors-description = Learn every hawk and eagle
+
+## Archive
+see-the-archive = Play past daily packs
+daily-pack-archive = Daily Pack Archive
+daily-pack-archive-intro = Missed a day? Play it here for XP. Only packs played on their own day count toward your streak.
+previous-month = Previous
+next-month = Next
+archive-month = { $month ->
+    [1] January
+    [2] February
+    [3] March
+    [4] April
+    [5] May
+    [6] June
+    [7] July
+    [8] August
+    [9] September
+    [10] October
+    [11] November
+   *[12] December
+} { $year }
+calendar-completed-late = Completed later
+play-daily-pack-of = Play the daily pack of { $date }
+
+## Game
+continue = Continue
+nice-work = Nice work!
+stat-xp = XP
+stat-birds-learned = Birds Learned
+stat-daily-pack-streak = Daily Pack Streak
+sign-up-to-save = Sign up to save your progress!
+
+## Media attribution
+photo-credit = Photo
+audio-credit = Audio
+source = source
+
+## Auth
+enter-email = Enter your email
+login-signup = Login / Signup
+magic-link-sent = We sent a magic link to your email! Check your inbox and click the link to login.
+login-successful = Login successful!
+logging-in = Logging in...
@@ -0,0 +1,170 @@
+## Layout
+copyright = © 2024 birdtalk
+github-repository = Repositorio en GitHub
+your-aviary = Tu pajarera
+donate = Donar
+language = Idioma
+close-modal = Cerrar ventana
+
+## Landing page
+tagline-daily = 10 aves nuevas cada día
+tagline-game = Un juego que te ayuda a memorizar cantos de aves.
+wild-speaks = La naturaleza habla.
+wild-speaks-body = Una de las mejores maneras de encontrar un ave es oírla primero. Aprende a reconocer nuevos cantos aquí para tu próxima aventura en el campo.
+
+## Errors
+uh-oh = ¡Ay, no! 😱
+github-issue = issue en GitHub
+report-error = Por favor abre un { $link } con el siguiente error:
+error-fetching-daily-pack = Algo salió mal al cargar el desafío de hoy.
+error-fetching-birds = ¡Algo salió mal al cargar tus aves!
+error-no-daily-pack = ¡Ay, no! ¡No encontramos el paquete de hoy!
+error-no-pack = No se encontró ningún paquete con id { $id } 🙈
+error-birds-not-learned = ¡Todavía no has aprendido algunas de estas aves!
+error-network = No pudimos conectar con el servidor. Revisa tu conexión e inténtalo de nuevo.
+error-unauthorized = No tienes permiso para hacer eso. Intenta iniciar sesión de nuevo.
+error-server = Algo salió mal de nuestro lado. Por favor inténtalo más tarde.
+retry = Reintentar
+error-invalid-config = birdtalk está mal configurado, así que no puede cargar ninguna ave.
+
+## Pack of the day
+next-bird = Siguiente ave
+play = jugar
+pause = Pausar
+play-audio = Reproducir
+audio-not-supported = Tu navegador no soporta el elemento de audio.
+pack-of-the-day = Paquete del día
+
+## Packs
+packs = Paquetes
+browse-packs = Ver más paquetes
+pack-bird-count = { $count ->
+    [one] 1 ave
+   *[other] { $count } aves
+}
+locked = Bloqueado
+premium-pitch = ¡Desbloquea todos los paquetes para aprender aún más aves y apoyar a birdtalk!
+unlock-all-packs = Desbloquear todos los paquetes
+unlock-pack = Desbloquear paquete
+pack-locked-title = Este paquete está bloqueado 🔒
+pack-locked = Desbloquea todos los paquetes para jugarlo.
+sign-in-to-unlock = Inicia sesión primero, para que tus paquetes sigan desbloqueados en todos tus dispositivos.
+error-fetching-packs = ¡Algo salió mal al cargar los paquetes!
+error-checkout = No pudimos iniciar el pago. Por favor inténtalo más tarde.
+
+## Aviary
+empty-nest = Un nido vacío 🪹
+empty-nest-body = ¡Necesitas aprender más aves para llenar tu pajarera!
+play-to-get-started = Juega el { $link } para empezar.
+aviary-intro = ¡Aquí están todas las aves que has aprendido hasta ahora! 🐦 Sigue jugando el { $link } para aprender más.
+aviary-review-locked = ¡Cuando hayas aprendido { $count } aves, vuelve aquí para jugar una ronda de repaso!
+select-birds-to-review = Selecciona { $count } aves para repasar
+select-more-birds = { $count ->
+    [one] Selecciona 1 ave más
+   *[other] Selecciona { $count } aves más
+}
+review = repasar
+average-recognition-time = Reconocida en { $seconds } s de media
+most-confused-pairs = Parejas más confundidas
+times-confused = { $count ->
+    [one] 1 vez
+   *[other] { $count } veces
+}
+review-confused = Repasar estas aves
+
+## Progress
+your-progress = Tu progreso
+see-your-progress = Mira tu progreso a lo largo del tiempo
+no-progress-yet = ¡Juega una partida y tu progreso aparecerá aquí!
+chart-birds-learned = Aves aprendidas: { $count }
+chart-accuracy = Precisión: { $percent }%
+daily-pack-calendar = { $count ->
+    [one] 1 paquete diario completado
+   *[other] { $count } paquetes diarios completados
+}
+calendar-played = Jugado
+calendar-daily-pack = Paquete diario completado
+calendar-streak-freeze = Congelación de racha usada
+streak-freezes = { $count ->
+    [one] Tienes 1 congelación de racha
+   *[other] Tienes { $count } congelaciones de racha
+}. Gana una cada { $days } días seguidos y mantén tu racha cuando te saltes un día.
+streak-time-zone = Los días se cuentan en
+use-device-time-zone = Usar la zona horaria de este dispositivo ({ $zone })
+family-mastery = Aves aprendidas por familia
+unknown-family = Otras aves
+
+## Trophies
+see-your-trophies = Mira tus trofeos
+trophies = Trofeos
+trophies-unlocked = { $count } de { $total } desbloqueados
+unlocked-on = Desbloqueado el { $date }
+achievements-unlocked = { $count ->
+    [one] ¡Logro desbloqueado!
+   *[other] ¡{ $count } logros desbloqueados!
+}
+achievement-first-bird = Primera pluma
+achievement-first-bird-description = Aprende tu primera ave
+achievement-flock = Bandada
+achievement-flock-description = Aprende 25 aves
+achievement-aviary = Pajarera llena
+achievement-aviary-description = Aprende 100 aves
+achievement-daily-streak-3 = Madrugador
+achievement-daily-streak-3-description = Completa el paquete diario 3 días seguidos
+achievement-daily-streak-7 = Animal de costumbres
+achievement-daily-streak-7-description = Completa el paquete diario 7 días seguidos
+achievement-daily-streak-30 = Migración
+achievement-daily-streak-30-description = Completa el paquete diario 30 días seguidos
+achievement-streak-10 = Buen oído
+achievement-streak-10-description = Identifica 10 aves seguidas
+achievement-streak-25 = Oído absoluto
+achievement-streak-25-description = Identifica 25 aves seguidas
+achievement-owls = Búho nocturno
+achievement-owls-description = Aprende todos los búhos
+achievement-woodpeckers = Toc toc
+achievement-woodpeckers-description = Aprende todos los pájaros carpinteros
+achievement-raptors = Aves rapaces
+achievement-raptors-description = Aprende todos los halcones y águilas
+
+## Archive
+see-the-archive = Juega paquetes diarios anteriores
+daily-pack-archive = Archivo de paquetes diarios
+daily-pack-archive-intro = ¿Te saltaste un día? Juégalo aquí para ganar XP. Solo los paquetes jugados en su propio día cuentan para tu racha.
+previous-month = Anterior
+next-month = Siguiente
+archive-month = { $month ->
+    [1] enero
+    [2] febrero
+    [3] marzo
+    [4] abril
+    [5] mayo
+    [6] junio
+    [7] julio
+    [8] agosto
+    [9] septiembre
+    [10] octubre
+    [11] noviembre
+   *[12] diciembre
+} de { $year }
+calendar-completed-late = Completado más tarde
+play-daily-pack-of = Juega el paquete diario del { $date }
+
+## Game
+continue = Continuar
+nice-work = ¡Buen trabajo!
+stat-xp = XP
+stat-birds-learned = Aves aprendidas
+stat-daily-pack-streak = Racha de paquetes diarios
+sign-up-to-save = ¡Regístrate para guardar tu progreso!
+
+## Media attribution
+photo-credit = Foto
+audio-credit = Audio
+source = fuente
+
+## Auth
+enter-email = Ingresa tu correo electrónico
+login-signup = Iniciar sesión / Registrarse
+magic-link-sent = ¡Te enviamos un enlace mágico a tu correo! Revisa tu bandeja de entrada y haz clic en el enlace para iniciar sesión.
+login-successful = ¡Sesión iniciada!
+logging-in = Iniciando sesión...
@@ -0,0 +1,196 @@
+//! Milestones to reward beyond XP and levels.
+//!
+//! Achievements are declared in [`ACHIEVEMENTS`] and checked after every game (see
+//! [`Stats::unlock_achievements`]). Once unlocked they're kept in [`Stats`] for good, even if e.g.
+//! the streak that unlocked them is lost.
+
+use std::collections::HashMap;
+
+use crate::{pack::Pack, stats::Stats, t};
+
+/// Ids of every bird of a family, by family, for the families that achievements are for (see
+/// [`Achievement::families_in`]).
+pub type FamilyBirds = HashMap<String, Vec<u64>>;
+
+/// What it takes to unlock an achievement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criterion {
+    /// Learning at least this many birds
+    BirdsLearned(u32),
+    /// Completing the daily pack this many days in a row
+    DailyPackStreak(u32),
+    /// Identifying this many birds correctly in a row
+    RecordStreak(u32),
+    /// Learning every bird of a family, e.g. all of the owls
+    FamilyLearned(&'static str),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Achievement {
+    /// Identifies the achievement in stats, so it must never change. Its name and description
+    /// are the messages `achievement-{id}` and `achievement-{id}-description`.
+    pub id: &'static str,
+    pub criterion: Criterion,
+}
+
+/// Every achievement, in the order they're shown.
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first-bird",
+        criterion: Criterion::BirdsLearned(1),
+    },
+    Achievement {
+        id: "flock",
+        criterion: Criterion::BirdsLearned(25),
+    },
+    Achievement {
+        id: "aviary",
+        criterion: Criterion::BirdsLearned(100),
+    },
+    Achievement {
+        id: "daily-streak-3",
+        criterion: Criterion::DailyPackStreak(3),
+    },
+    Achievement {
+        id: "daily-streak-7",
+        criterion: Criterion::DailyPackStreak(7),
+    },
+    Achievement {
+        id: "daily-streak-30",
+        criterion: Criterion::DailyPackStreak(30),
+    },
+    Achievement {
+        id: "streak-10",
+        criterion: Criterion::RecordStreak(10),
+    },
+    Achievement {
+        id: "streak-25",
+        criterion: Criterion::RecordStreak(25),
+    },
+    Achievement {
+        id: "owls",
+        criterion: Criterion::FamilyLearned("Owls"),
+    },
+    Achievement {
+        id: "woodpeckers",
+        criterion: Criterion::FamilyLearned("Woodpeckers"),
+    },
+    Achievement {
+        id: "raptors",
+        criterion: Criterion::FamilyLearned("Hawks and Eagles"),
+    },
+];
+
+impl Achievement {
+    pub fn by_id(id: &str) -> Option<&'static Achievement> {
+        ACHIEVEMENTS.iter().find(|a| a.id == id)
+    }
+
+    pub fn name(&self) -> String {
+        t!(&format!("achievement-{}", self.id))
+    }
+
+    pub fn description(&self) -> String {
+        t!(&format!("achievement-{}-description", self.id))
+    }
+
+    /// Families of `pack`'s birds that achievements are for. Only completing a pack with birds of
+    /// a family can unlock its achievement, so these are the families whose birds must be known
+    /// to check achievements after the pack (see [`Achievement::is_met`]).
+    pub fn families_in(pack: &Pack) -> Vec<&'static str> {
+        ACHIEVEMENTS
+            .iter()
+            .filter_map(|a| match a.criterion {
+                Criterion::FamilyLearned(family) => Some(family),
+                _ => None,
+            })
+            .filter(|family| {
+                pack.birds
+                    .iter()
+                    .any(|b| b.family.as_deref() == Some(*family))
+            })
+            .collect()
+    }
+
+    /// Whether `stats` meet the criterion, having just completed `pack`. `families` are the birds
+    /// of [`Achievement::families_in`] the pack; achievements of families that are missing aren't
+    /// met.
+    pub fn is_met(&self, stats: &Stats, pack: &Pack, families: &FamilyBirds) -> bool {
+        match self.criterion {
+            Criterion::BirdsLearned(count) => stats.total_birds_learned() >= count,
+            Criterion::DailyPackStreak(days) => stats.active_daily_pack_streak() >= days,
+            Criterion::RecordStreak(count) => stats.record_streak() >= count,
+            Criterion::FamilyLearned(family) => {
+                Self::families_in(pack).contains(&family)
+                    && families.get(family).is_some_and(|birds| {
+                        !birds.is_empty()
+                            && birds
+                                .iter()
+                                .all(|id| stats.bird_stats().get(id).is_some_and(|bs| bs.learned))
+                    })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use chrono::{DateTime, Duration};
+
+    use super::*;
+    use crate::{
+        bird::Bird,
+        pack::PackIdentifier,
+        stats::{Answer, Mode, LEARN_THRESHOLD},
+    };
+
+    fn owl(id: u64) -> Bird {
+        Bird {
+            family: Some("Owls".to_string()),
+            ..Bird::fixture(id)
+        }
+    }
+
+    fn learn(stats: &mut Stats, bird_id: u64) {
+        let start = DateTime::from_timestamp(0, 0).unwrap();
+        for _ in 0..LEARN_THRESHOLD {
+            stats.record_answer(Answer {
+                at: start + Duration::seconds(stats.log().len() as i64),
+                game_started_at: start,
+                bird_id,
+                pack: PackIdentifier::Id(1),
+                mode: Mode::MultipleChoice,
+                response_time_ms: 3000,
+                chosen: bird_id,
+            });
+        }
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        let ids: HashSet<_> = ACHIEVEMENTS.iter().map(|a| a.id).collect();
+        assert_eq!(ids.len(), ACHIEVEMENTS.len());
+    }
+
+    #[test]
+    fn families_are_learned_in_full() {
+        let owls = Achievement::by_id("owls").unwrap();
+        // the pack only has some of the owls
+        let pack = Pack::from(vec![owl(1), owl(2), Bird::fixture(4)]);
+        assert_eq!(Achievement::families_in(&pack), ["Owls"]);
+        let families = FamilyBirds::from([("Owls".to_string(), vec![1, 2, 3])]);
+
+        let mut stats = Stats::default();
+        learn(&mut stats, 1);
+        learn(&mut stats, 2);
+        assert!(!owls.is_met(&stats, &pack, &families));
+        // nor can it be met without knowing every owl
+        assert!(!owls.is_met(&stats, &pack, &FamilyBirds::new()));
+
+        learn(&mut stats, 3);
+        assert!(owls.is_met(&stats, &pack, &families));
+        assert!(!owls.is_met(&stats, &Pack::from(vec![Bird::fixture(4)]), &families));
+    }
+}
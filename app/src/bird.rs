@@ -1,24 +1,112 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
 use chrono::NaiveDate;
+use futures_util::future::join_all;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    supabase::{self, Error, Result, SupabaseResource},
-    utils,
+    achievements::FamilyBirds,
+    clock::Clock,
+    datasource::{self, BirdPages},
+    supabase::{self, ImageTransform, Result},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq)]
 pub struct Bird {
     pub id: u64,
+    /// Stable eBird/Clements species code, e.g. "norcar". Unlike the scientific name, this doesn't
+    /// change with taxonomic updates.
+    #[serde(default)]
+    pub species_code: Option<String>,
     pub common_name: String,
+    /// Common names keyed by locale, e.g. "es" or "es-MX"
+    #[serde(default)]
+    pub common_names: HashMap<String, String>,
     pub scientific_name: String,
+    /// The taxonomic family, e.g. "Cardinals, Grosbeaks and Buntings"
+    #[serde(default)]
+    pub family: Option<String>,
     pub image: String,
+    #[serde(default)]
+    pub image_attribution: Option<Attribution>,
     pub sounds: Vec<Sound>,
+    /// Signed URLs of the bird's media, since it isn't public (see [`Bird::sign_media`])
+    #[serde(skip)]
+    pub signed: SignedMedia,
+}
+
+/// Signed URLs of a bird's image, at each size it's shown at, and of its default sound.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SignedMedia {
+    pub images: HashMap<ImageSize, String>,
+    pub sound: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct Sound {
     pub path: String,
     pub default_: bool,
+    /// The kind of vocalization, e.g. "song" or "call"
+    #[serde(default)]
+    pub type_: Option<String>,
+    #[serde(default)]
+    pub attribution: Option<Attribution>,
+    /// How long the recording plays for, if known
+    #[serde(default)]
+    pub duration_ms: Option<u32>,
+}
+
+/// Licensing and attribution metadata for a piece of media (an image or a recording).
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Attribution {
+    /// License identifier, e.g. "CC BY-NC-SA 4.0"
+    pub license: Option<String>,
+    /// The photographer or recordist
+    pub author: Option<String>,
+    /// Where the media came from, e.g. a xeno-canto or Macaulay Library catalog page
+    pub source_url: Option<String>,
+}
+
+impl Attribution {
+    pub fn is_empty(&self) -> bool {
+        self.license.is_none() && self.author.is_none() && self.source_url.is_none()
+    }
+}
+
+impl Display for Attribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.author, &self.license) {
+            (Some(author), Some(license)) => write!(f, "© {author} ({license})"),
+            (Some(author), None) => write!(f, "© {author}"),
+            (None, Some(license)) => write!(f, "{license}"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Sizes that bird images are shown at. Images are resized to twice their CSS width, for high
+/// density screens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageSize {
+    /// e.g. on a `BirdCard`, `w-24`
+    Small,
+    /// e.g. the game's choices, up to `w-28`
+    Medium,
+}
+
+impl ImageSize {
+    pub const ALL: [Self; 2] = [Self::Small, Self::Medium];
+
+    pub fn transform(self) -> ImageTransform {
+        let width = match self {
+            Self::Small => 192,
+            Self::Medium => 224,
+        };
+        ImageTransform::width(width).quality(75)
+    }
 }
 
 impl PartialEq for Bird {
@@ -34,28 +122,133 @@ impl std::hash::Hash for Bird {
 }
 
 impl Bird {
-    /// Get image URL hosted by Supabase storage, e.g. http://127.0.0.1:54321/storage/v1/object/public/bird_images/cardinalis-cardinalis/unlicensed-optimized.jpg
-    pub fn image_url(&self) -> String {
-        supabase::storage_object_url(&self.image)
+    /// Get the common name for the given locale, falling back to its base language (e.g. "es" for
+    /// "es-MX") and then the default (English) common name.
+    pub fn common_name_for(&self, locale: &str) -> &str {
+        let language = locale.split(['-', '_']).next().unwrap_or(locale);
+        self.common_names
+            .get(locale)
+            .or_else(|| self.common_names.get(language))
+            .unwrap_or(&self.common_name)
+    }
+
+    /// Get image URL, resized for where it's shown, e.g. http://127.0.0.1:54321/storage/v1/render/image/sign/bird_images/cardinalis-cardinalis/unlicensed-optimized.jpg?token=...&width=192&quality=75
+    pub fn image_url_sized(&self, size: ImageSize) -> String {
+        self.signed
+            .images
+            .get(&size)
+            .cloned()
+            .unwrap_or_else(|| supabase::unsigned_url(&self.image, Some(&size.transform())))
+    }
+
+    /// The default sound for this bird. Birds may have other recordings, e.g. calls in addition to
+    /// a song.
+    pub fn default_sound(&self) -> &Sound {
+        self.sounds
+            .iter()
+            .find(|s| s.default_)
+            .unwrap_or(&self.sounds[0])
     }
 
     pub fn default_sound_url(&self) -> String {
-        supabase::storage_object_url(&self.sounds[0].path)
+        self.signed
+            .sound
+            .clone()
+            .unwrap_or_else(|| supabase::unsigned_url(&self.default_sound().path, None))
     }
 
-    /// Query db for birds by id
-    // TODO: enforce global limit? I think supabase limits 1000 by default.
+    /// Sign the URLs of the image, at each size, and of the default sound, since bird media isn't
+    /// public. Media the user may not read is left unsigned.
+    pub async fn sign_media(birds: &mut [Bird]) -> Result<()> {
+        let sounds = birds.iter().map(|b| b.default_sound().path.clone());
+        let sounds = supabase::sign_all(sounds, supabase::SIGNED_URL_EXPIRY).await?;
+        // images are signed one at a time, since transformations can't be signed in bulk
+        let images = birds.iter().flat_map(|bird| {
+            ImageSize::ALL.map(|size| async move {
+                let transform = size.transform();
+                let url =
+                    supabase::sign(&bird.image, supabase::SIGNED_URL_EXPIRY, Some(&transform));
+                (bird.id, size, url.await)
+            })
+        });
+        let mut signed: HashMap<u64, SignedMedia> = HashMap::new();
+        for (id, size, url) in join_all(images).await {
+            match url {
+                Ok(url) => {
+                    signed.entry(id).or_default().images.insert(size, url);
+                }
+                Err(e) if e.is_transient() => return Err(e),
+                Err(e) => tracing::debug!("{e}"),
+            }
+        }
+        for bird in birds {
+            bird.signed = SignedMedia {
+                sound: sounds.get(&bird.default_sound().path).cloned(),
+                ..signed.remove(&bird.id).unwrap_or_default()
+            };
+        }
+        Ok(())
+    }
+
+    /// Attribution for the image, if any is known.
+    pub fn image_attribution(&self) -> Option<&Attribution> {
+        self.image_attribution.as_ref().filter(|a| !a.is_empty())
+    }
+
+    /// Attribution for the default sound, if any is known.
+    pub fn default_sound_attribution(&self) -> Option<&Attribution> {
+        self.default_sound()
+            .attribution
+            .as_ref()
+            .filter(|a| !a.is_empty())
+    }
+
+    /// Fetch birds by id
     pub async fn fetch_by_ids<I>(ids: I) -> Result<Vec<Self>>
     where
         I: IntoIterator<Item = u64>,
     {
-        Self::request().select("*").in_("id", ids).execute().await
+        datasource::source()
+            .birds_by_ids(ids.into_iter().collect())
+            .await
+    }
+
+    /// Fetch the ids of every bird of the given families, by family
+    pub async fn fetch_family_ids<I, S>(families: I) -> Result<FamilyBirds>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        datasource::source()
+            .birds_of_families(families.into_iter().map(Into::into).collect())
+            .await
+    }
+
+    /// Fetch birds by id, `page_size` birds at a time
+    pub fn pages_by_ids<I>(ids: I, page_size: usize) -> BirdPages
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        BirdPages::new(ids.into_iter().collect(), page_size)
     }
 }
 
-impl SupabaseResource for Bird {
-    fn table_name() -> &'static str {
-        "birds_detailed"
+#[cfg(test)]
+impl Bird {
+    /// A bird with nothing but an id, for tests.
+    pub fn fixture(id: u64) -> Self {
+        Self {
+            id,
+            species_code: None,
+            common_name: String::new(),
+            common_names: HashMap::new(),
+            scientific_name: String::new(),
+            family: None,
+            image: String::new(),
+            image_attribution: None,
+            sounds: Vec::new(),
+            signed: SignedMedia::default(),
+        }
     }
 }
 
@@ -66,6 +259,14 @@ pub struct BirdPack {
     pub description: String,
     pub birds: Vec<Bird>,
     pub day: Option<NaiveDate>,
+    /// Whether anyone may play the pack, and sign URLs of its media. Other packs need an
+    /// entitlement, see [`crate::payments`].
+    #[serde(default = "default_free")]
+    pub free: bool,
+}
+
+fn default_free() -> bool {
+    true
 }
 
 impl PartialEq for BirdPack {
@@ -74,37 +275,24 @@ impl PartialEq for BirdPack {
     }
 }
 
-impl SupabaseResource for BirdPack {
-    fn table_name() -> &'static str {
-        "bird_packs_detailed"
-    }
-}
-
 impl BirdPack {
-    /// Query db for bird pack by id
+    /// Fetch bird pack by id
     pub async fn fetch_by_id(id: u64) -> Result<Self> {
-        Self::request()
-            .select("*")
-            .eq("id", id.to_string())
-            .execute()
-            .await?
-            .pop()
-            .ok_or_else(|| Error::from(format!("No pack found with id {id} 🙈")))
+        datasource::source().pack_by_id(id).await
+    }
+
+    /// Fetch every pack, other than daily packs
+    pub async fn fetch_all() -> Result<Vec<Self>> {
+        datasource::source().packs().await
     }
 
-    /// Query db for pack of today (respects local time)
+    /// Fetch pack of today (respects local time)
     pub async fn fetch_today() -> Result<Self> {
-        Self::fetch_by_day(utils::today()).await
+        Self::fetch_by_day(Clock::System.today()).await
     }
 
-    /// Query db for pack of a given day (respects local time)
+    /// Fetch pack of a given day (respects local time)
     pub async fn fetch_by_day(day: NaiveDate) -> Result<Self> {
-        Self::request()
-            .select("*")
-            .eq("day", day.format("%Y-%m-%d"))
-            .execute()
-            .await?
-            .pop()
-            .ok_or_else(|| supabase::Error::NoDailyPack)
+        datasource::source().daily_pack(day).await
     }
 }
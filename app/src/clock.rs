@@ -0,0 +1,41 @@
+//! The current time, which can be fixed so that anything depending on it (e.g. streaks, which
+//! depend on what day it is) can be tested.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Clock {
+    /// The system's clock
+    #[default]
+    System,
+    /// Always the given time
+    Fixed(DateTime<Utc>),
+}
+
+impl Clock {
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Clock::System => Utc::now(),
+            Clock::Fixed(at) => *at,
+        }
+    }
+
+    /// Move a fixed clock on by `duration`. The system's clock moves on by itself.
+    pub fn advance(&mut self, duration: Duration) {
+        if let Clock::Fixed(at) = self {
+            *at += duration;
+        }
+    }
+
+    /// What day it is in the device's time zone. Streaks count days in the user's time zone
+    /// instead, see [`crate::stats::Stats::today`].
+    pub fn today(&self) -> NaiveDate {
+        self.now().with_timezone(&Local).date_naive()
+    }
+
+    /// The device's time zone, e.g. America/New_York, if it's known.
+    pub fn local_time_zone(&self) -> Option<Tz> {
+        iana_time_zone::get_timezone().ok()?.parse().ok()
+    }
+}
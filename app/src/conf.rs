@@ -1,7 +1,271 @@
-use dotenvy_macro::dotenv;
+//! Runtime configuration.
+//!
+//! On the web, config is loaded at startup from a `config.json` served alongside the app, so
+//! that the same build can be promoted from staging to production. Missing values fall back to
+//! environment variables (when not on the web) and then to those set at compile time, e.g. from
+//! `.env` via `just`.
 
-pub const SUPABASE_API_URL: &str = dotenv!("SUPABASE_API_URL");
-pub const SUPABASE_ANON_KEY: &str = dotenv!("SUPABASE_ANON_KEY");
-pub const SUPABASE_STORAGE_URL: &str = dotenv!("SUPABASE_STORAGE_URL");
+use std::collections::BTreeMap;
 
-pub const APP_URL: &str = dotenv!("APP_URL");
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Where the config is served from, relative to the app.
+pub const CONFIG_PATH: &str = "/config.json";
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
+static INIT_ERROR: OnceCell<String> = OnceCell::new();
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub supabase_api_url: String,
+    pub supabase_anon_key: String,
+    /// The Supabase storage API, e.g. `https://<project>.supabase.co/storage/v1`. Defaults to the
+    /// one under [`Config::supabase_api_url`].
+    pub supabase_storage_url: Option<String>,
+    /// A CDN in front of storage, serving the same paths, for signed (and transformed) media
+    pub storage_cdn_url: Option<String>,
+    /// A hosted checkout page for premium packs, see [`crate::payments::link`]
+    pub payment_link_url: Option<String>,
+    pub app_url: String,
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to fetch {CONFIG_PATH}: {0}")]
+    Fetch(String),
+    #[error("Invalid {CONFIG_PATH}: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Missing config value `{0}`")]
+    Missing(&'static str),
+    #[error("Config value `{key}` should be an http(s) URL, but is {value:?}")]
+    InvalidUrl { key: &'static str, value: String },
+}
+
+/// Config values as found in one place, to be merged with fallbacks.
+#[derive(Debug, Default, Deserialize)]
+struct PartialConfig {
+    supabase_api_url: Option<String>,
+    supabase_anon_key: Option<String>,
+    supabase_storage_url: Option<String>,
+    storage_cdn_url: Option<String>,
+    payment_link_url: Option<String>,
+    app_url: Option<String>,
+    /// Values that aren't known, e.g. misspelled ones, or those of another version of the app.
+    /// They're only warned about, so that a config can be deployed ahead of the app.
+    #[serde(flatten)]
+    unknown: BTreeMap<String, serde_json::Value>,
+}
+
+impl PartialConfig {
+    fn compile_time() -> Self {
+        Self {
+            supabase_api_url: option_env!("SUPABASE_API_URL").map(String::from),
+            supabase_anon_key: option_env!("SUPABASE_ANON_KEY").map(String::from),
+            supabase_storage_url: option_env!("SUPABASE_STORAGE_URL").map(String::from),
+            storage_cdn_url: option_env!("STORAGE_CDN_URL").map(String::from),
+            payment_link_url: option_env!("PAYMENT_LINK_URL").map(String::from),
+            app_url: option_env!("APP_URL").map(String::from),
+            unknown: BTreeMap::new(),
+        }
+    }
+
+    #[cfg(not(feature = "web"))]
+    fn env() -> Self {
+        let var = |key| std::env::var(key).ok();
+        Self {
+            supabase_api_url: var("SUPABASE_API_URL"),
+            supabase_anon_key: var("SUPABASE_ANON_KEY"),
+            supabase_storage_url: var("SUPABASE_STORAGE_URL"),
+            storage_cdn_url: var("STORAGE_CDN_URL"),
+            payment_link_url: var("PAYMENT_LINK_URL"),
+            app_url: var("APP_URL"),
+            unknown: BTreeMap::new(),
+        }
+    }
+
+    /// Fill in missing (or empty) values from `fallback`.
+    fn or(self, fallback: Self) -> Self {
+        let or = |value: Option<String>, fallback| value.filter(|v| !v.is_empty()).or(fallback);
+        Self {
+            supabase_api_url: or(self.supabase_api_url, fallback.supabase_api_url),
+            supabase_anon_key: or(self.supabase_anon_key, fallback.supabase_anon_key),
+            supabase_storage_url: or(self.supabase_storage_url, fallback.supabase_storage_url),
+            storage_cdn_url: or(self.storage_cdn_url, fallback.storage_cdn_url),
+            payment_link_url: or(self.payment_link_url, fallback.payment_link_url),
+            app_url: or(self.app_url, fallback.app_url),
+            unknown: self.unknown,
+        }
+    }
+
+    fn validate(self) -> Result<Config, ConfigError> {
+        let url = |key, value: Option<String>| match value.filter(|v| !v.is_empty()) {
+            None => Err(ConfigError::Missing(key)),
+            Some(v) if v.starts_with("http://") || v.starts_with("https://") => {
+                Ok(v.trim_end_matches('/').to_string())
+            }
+            Some(value) => Err(ConfigError::InvalidUrl { key, value }),
+        };
+        Ok(Config {
+            supabase_api_url: url("supabase_api_url", self.supabase_api_url)?,
+            supabase_anon_key: self
+                .supabase_anon_key
+                .filter(|v| !v.is_empty())
+                .ok_or(ConfigError::Missing("supabase_anon_key"))?,
+            // the S3 endpoint used to be configured here, which lives under the storage API
+            supabase_storage_url: self
+                .supabase_storage_url
+                .filter(|v| !v.is_empty())
+                .map(|v| url("supabase_storage_url", Some(v)))
+                .transpose()?
+                .map(|v| v.trim_end_matches("/s3").to_string()),
+            storage_cdn_url: self
+                .storage_cdn_url
+                .filter(|v| !v.is_empty())
+                .map(|v| url("storage_cdn_url", Some(v)))
+                .transpose()?,
+            payment_link_url: self
+                .payment_link_url
+                .filter(|v| !v.is_empty())
+                .map(|v| url("payment_link_url", Some(v)))
+                .transpose()?,
+            app_url: url("app_url", self.app_url)?,
+        })
+    }
+}
+
+/// Load and validate the config. This should be called once at startup, before anything uses
+/// [`get`].
+#[cfg(feature = "web")]
+pub async fn init() -> Result<&'static Config, ConfigError> {
+    set(fetch().await.map(Option::unwrap_or_default))
+}
+
+/// Load and validate the config. This should be called once at startup, before anything uses
+/// [`get`].
+#[cfg(not(feature = "web"))]
+pub fn init() -> Result<&'static Config, ConfigError> {
+    set(Ok(PartialConfig::env()))
+}
+
+fn set(config: Result<PartialConfig, ConfigError>) -> Result<&'static Config, ConfigError> {
+    let config = config.and_then(|config| config.or(PartialConfig::compile_time()).validate());
+    match config {
+        Ok(config) => Ok(CONFIG.get_or_init(|| config)),
+        Err(e) => {
+            INIT_ERROR.set(e.to_string()).ok();
+            Err(e)
+        }
+    }
+}
+
+/// Why [`init`] failed, if it did.
+pub fn init_error() -> Option<&'static str> {
+    INIT_ERROR.get().map(String::as_str)
+}
+
+/// Fetch the served config, if there is any.
+#[cfg(feature = "web")]
+async fn fetch() -> Result<Option<PartialConfig>, ConfigError> {
+    let rsp = gloo_net::http::Request::get(CONFIG_PATH)
+        .send()
+        .await
+        .map_err(|e| ConfigError::Fetch(e.to_string()))?;
+    // dev servers tend to serve the app for unknown paths
+    let is_json = rsp
+        .headers()
+        .get("content-type")
+        .is_some_and(|t| t.contains("json"));
+    if rsp.status() == 404 || (rsp.ok() && !is_json) {
+        tracing::debug!("No {CONFIG_PATH}, using fallbacks");
+        return Ok(None);
+    }
+    if !rsp.ok() {
+        return Err(ConfigError::Fetch(format!("HTTP {}", rsp.status())));
+    }
+    let body = rsp
+        .text()
+        .await
+        .map_err(|e| ConfigError::Fetch(e.to_string()))?;
+    let config: PartialConfig = serde_json::from_str(&body)?;
+    for key in config.unknown.keys() {
+        tracing::warn!("Ignoring unknown config value `{key}` in {CONFIG_PATH}");
+    }
+    Ok(Some(config))
+}
+
+/// The config. Before [`init`] (e.g. in tests), this is whatever was set at compile time.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        let fallback = PartialConfig::compile_time();
+        Config {
+            supabase_api_url: fallback.supabase_api_url.unwrap_or_default(),
+            supabase_anon_key: fallback.supabase_anon_key.unwrap_or_default(),
+            supabase_storage_url: fallback
+                .supabase_storage_url
+                .map(|v| v.trim_end_matches("/s3").to_string()),
+            storage_cdn_url: fallback.storage_cdn_url,
+            payment_link_url: fallback.payment_link_url,
+            app_url: fallback.app_url.unwrap_or_default(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partial(api_url: &str, anon_key: &str) -> PartialConfig {
+        PartialConfig {
+            supabase_api_url: Some(api_url.to_string()),
+            supabase_anon_key: Some(anon_key.to_string()),
+            supabase_storage_url: None,
+            storage_cdn_url: None,
+            payment_link_url: None,
+            app_url: Some("https://talkbirdyto.me/".to_string()),
+            unknown: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn falls_back_to_missing_values() {
+        let served: PartialConfig =
+            serde_json::from_str(r#"{"supabase_api_url": "https://staging.supabase.co"}"#).unwrap();
+        let config = served
+            .or(partial("http://127.0.0.1:54321", "key"))
+            .validate()
+            .unwrap();
+        assert_eq!(config.supabase_api_url, "https://staging.supabase.co");
+        assert_eq!(config.supabase_anon_key, "key");
+        assert_eq!(config.app_url, "https://talkbirdyto.me");
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        assert!(matches!(
+            partial("http://127.0.0.1:54321", "").validate(),
+            Err(ConfigError::Missing("supabase_anon_key"))
+        ));
+        assert!(matches!(
+            partial("127.0.0.1:54321", "key").validate(),
+            Err(ConfigError::InvalidUrl {
+                key: "supabase_api_url",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn ignores_unknown_values() {
+        let served: PartialConfig = serde_json::from_str(
+            r#"{"supabase_api_url": "https://staging.supabase.co", "api_url": ""}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            served.supabase_api_url.as_deref(),
+            Some("https://staging.supabase.co")
+        );
+        assert_eq!(served.unknown.keys().collect::<Vec<_>>(), ["api_url"]);
+    }
+}
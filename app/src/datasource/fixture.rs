@@ -0,0 +1,223 @@
+//! In-memory data built from the seed data in `data/seed`, so the app can run without a backend.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+
+use super::{DataSource, LocalBoxFuture};
+use crate::{
+    achievements::FamilyBirds,
+    bird::{Bird, BirdPack, Sound},
+    payments::Entitlement,
+    supabase::{Error, Result},
+    sync::UserStats,
+};
+
+const SEED_BIRDS: &str = include_str!("../../../data/seed/birds.json");
+const SEED_PACKS: &str = include_str!("../../../data/seed/packs.json");
+
+/// Number of birds in each daily pack
+const DAILY_PACK_SIZE: usize = 10;
+
+/// Daily packs get ids from here on, by day, so they can also be fetched by id.
+const DAILY_PACK_IDS: u64 = 1_000_000;
+
+#[derive(Deserialize)]
+struct SeedBird {
+    common_name: String,
+    scientific_name: String,
+    family: Option<String>,
+    default_sound_file: String,
+    img_file: String,
+}
+
+#[derive(Deserialize)]
+struct SeedPack {
+    name: String,
+    description: String,
+    /// Scientific names
+    birds: Vec<String>,
+    free: bool,
+}
+
+/// Fixed birds and packs, with daily packs cycling through the birds. Stats are only kept in
+/// memory.
+pub struct FixtureSource {
+    birds: Vec<Bird>,
+    packs: Vec<BirdPack>,
+    stats: RefCell<HashMap<String, UserStats>>,
+}
+
+impl Default for FixtureSource {
+    /// The seed data
+    fn default() -> Self {
+        let birds: Vec<SeedBird> = serde_json::from_str(SEED_BIRDS).expect("valid seed birds");
+        let packs: Vec<SeedPack> = serde_json::from_str(SEED_PACKS).expect("valid seed packs");
+        let birds: Vec<Bird> = birds
+            .into_iter()
+            .zip(1..)
+            .map(|(seed, id)| Bird {
+                id,
+                species_code: None,
+                common_name: seed.common_name,
+                common_names: HashMap::new(),
+                scientific_name: seed.scientific_name,
+                family: seed.family,
+                image: format!("bird_images/{}", seed.img_file),
+                image_attribution: None,
+                sounds: vec![Sound {
+                    path: format!("bird_sounds/{}", seed.default_sound_file),
+                    default_: true,
+                    type_: None,
+                    attribution: None,
+                    duration_ms: None,
+                }],
+                signed: Default::default(),
+            })
+            .collect();
+        let packs = packs
+            .into_iter()
+            .zip(1..)
+            .map(|(seed, id)| BirdPack {
+                id,
+                name: seed.name,
+                description: seed.description,
+                birds: birds
+                    .iter()
+                    .filter(|bird| seed.birds.contains(&bird.scientific_name))
+                    .cloned()
+                    .collect(),
+                day: None,
+                free: seed.free,
+            })
+            .collect();
+        Self::new(birds, packs)
+    }
+}
+
+impl FixtureSource {
+    pub fn new(birds: Vec<Bird>, packs: Vec<BirdPack>) -> Self {
+        Self {
+            birds,
+            packs,
+            stats: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn birds(&self, ids: &[u64]) -> Vec<Bird> {
+        let mut birds: Vec<Bird> = self
+            .birds
+            .iter()
+            .filter(|bird| ids.contains(&bird.id))
+            .cloned()
+            .collect();
+        birds.sort_by_key(|bird| bird.id);
+        birds
+    }
+
+    fn pack(&self, id: u64) -> Option<BirdPack> {
+        if let Some(pack) = self.packs.iter().find(|pack| pack.id == id) {
+            return Some(pack.clone());
+        }
+        let days = i32::try_from(id.checked_sub(DAILY_PACK_IDS)?).ok()?;
+        self.daily(NaiveDate::from_num_days_from_ce_opt(days)?)
+    }
+
+    /// The next [`DAILY_PACK_SIZE`] birds each day, wrapping around.
+    fn daily(&self, day: NaiveDate) -> Option<BirdPack> {
+        if self.birds.is_empty() {
+            return None;
+        }
+        let days = day.num_days_from_ce();
+        let start = days as usize * DAILY_PACK_SIZE;
+        let size = DAILY_PACK_SIZE.min(self.birds.len());
+        Some(BirdPack {
+            id: DAILY_PACK_IDS + days as u64,
+            name: format!("Daily Bevy for {day}"),
+            description: format!("The daily challenge for {}", day.format("%A, %B %-d %Y")),
+            birds: (start..start + size)
+                .map(|ix| self.birds[ix % self.birds.len()].clone())
+                .collect(),
+            day: Some(day),
+            free: true,
+        })
+    }
+}
+
+impl DataSource for FixtureSource {
+    fn birds_by_ids(&self, ids: Vec<u64>) -> LocalBoxFuture<'_, Result<Vec<Bird>>> {
+        Box::pin(async move { Ok(self.birds(&ids)) })
+    }
+
+    fn birds_of_families(&self, families: Vec<String>) -> LocalBoxFuture<'_, Result<FamilyBirds>> {
+        Box::pin(async move {
+            let mut birds = FamilyBirds::new();
+            for bird in &self.birds {
+                if let Some(family) = bird.family.as_ref().filter(|f| families.contains(f)) {
+                    birds.entry(family.clone()).or_default().push(bird.id);
+                }
+            }
+            Ok(birds)
+        })
+    }
+
+    fn pack_by_id(&self, id: u64) -> LocalBoxFuture<'_, Result<BirdPack>> {
+        Box::pin(async move { self.pack(id).ok_or(Error::NoPack(id)) })
+    }
+
+    fn daily_pack(&self, day: NaiveDate) -> LocalBoxFuture<'_, Result<BirdPack>> {
+        Box::pin(async move { self.daily(day).ok_or(Error::NoDailyPack) })
+    }
+
+    fn packs(&self) -> LocalBoxFuture<'_, Result<Vec<BirdPack>>> {
+        Box::pin(async move { Ok(self.packs.clone()) })
+    }
+
+    // Nothing is paid for up front; the fake payment provider grants entitlements per session
+    fn entitlements(&self, _user_id: String) -> LocalBoxFuture<'_, Result<Vec<Entitlement>>> {
+        Box::pin(async move { Ok(Vec::new()) })
+    }
+
+    fn load_stats(&self, user_id: String) -> LocalBoxFuture<'_, Result<Option<UserStats>>> {
+        Box::pin(async move { Ok(self.stats.borrow().get(&user_id).cloned()) })
+    }
+
+    fn save_stats(&self, stats: UserStats) -> LocalBoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            self.stats.borrow_mut().insert(stats.user_id.clone(), stats);
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_seed_data() {
+        let source = FixtureSource::default();
+        assert!(source.birds.len() >= DAILY_PACK_SIZE);
+        assert!(source.packs.iter().all(|pack| !pack.birds.is_empty()));
+        assert_eq!(
+            source
+                .birds(&[3, 1, 999])
+                .iter()
+                .map(|b| b.id)
+                .collect::<Vec<_>>(),
+            [1, 3]
+        );
+    }
+
+    #[test]
+    fn cycles_through_birds_daily() {
+        let source = FixtureSource::default();
+        let day = NaiveDate::from_ymd_opt(2024, 9, 1).unwrap();
+        let today = source.daily(day).unwrap();
+        let tomorrow = source.daily(day.succ_opt().unwrap()).unwrap();
+        assert_eq!(today.birds.len(), DAILY_PACK_SIZE);
+        assert_ne!(today.birds, tomorrow.birds);
+        assert_eq!(source.pack(today.id).unwrap().birds, today.birds);
+    }
+}
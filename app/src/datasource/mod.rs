@@ -0,0 +1,117 @@
+//! Backend-agnostic data access.
+//!
+//! Models like [`Bird`] fetch their data through the current [`DataSource`], which is Supabase by
+//! default, or in-memory fixtures with the `fixtures` feature (e.g. for demos, UI tests or a fully
+//! offline build). Sources can also be swapped at runtime with [`set_source`].
+
+pub mod fixture;
+pub mod supabase;
+
+use std::{cell::RefCell, future::Future, pin::Pin, rc::Rc};
+
+use chrono::NaiveDate;
+
+use crate::{
+    achievements::FamilyBirds,
+    bird::{Bird, BirdPack},
+    payments::Entitlement,
+    supabase::Result,
+    sync::UserStats,
+};
+
+/// Futures returned by [`DataSource`], boxed so that sources can be swapped at runtime.
+pub type LocalBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+pub trait DataSource {
+    /// Birds with the given ids, ordered by id. Unknown ids are skipped.
+    fn birds_by_ids(&self, ids: Vec<u64>) -> LocalBoxFuture<'_, Result<Vec<Bird>>>;
+
+    /// Ids of every bird of the given families, by family
+    fn birds_of_families(&self, families: Vec<String>) -> LocalBoxFuture<'_, Result<FamilyBirds>>;
+
+    fn pack_by_id(&self, id: u64) -> LocalBoxFuture<'_, Result<BirdPack>>;
+
+    /// The pack of the day
+    fn daily_pack(&self, day: NaiveDate) -> LocalBoxFuture<'_, Result<BirdPack>>;
+
+    /// Every pack that isn't a daily pack, ordered by id. Their birds' media isn't signed, since
+    /// packs are only listed here; they're fetched by id to be played.
+    fn packs(&self) -> LocalBoxFuture<'_, Result<Vec<BirdPack>>>;
+
+    /// What the user has paid for
+    fn entitlements(&self, user_id: String) -> LocalBoxFuture<'_, Result<Vec<Entitlement>>>;
+
+    fn load_stats(&self, user_id: String) -> LocalBoxFuture<'_, Result<Option<UserStats>>>;
+
+    fn save_stats(&self, stats: UserStats) -> LocalBoxFuture<'_, Result<()>>;
+}
+
+thread_local! {
+    static SOURCE: RefCell<Rc<dyn DataSource>> = RefCell::new(default_source());
+}
+
+fn default_source() -> Rc<dyn DataSource> {
+    #[cfg(feature = "fixtures")]
+    {
+        Rc::new(fixture::FixtureSource::default())
+    }
+    #[cfg(not(feature = "fixtures"))]
+    {
+        Rc::new(supabase::SupabaseSource)
+    }
+}
+
+/// The data source in use.
+pub fn source() -> Rc<dyn DataSource> {
+    SOURCE.with(|source| source.borrow().clone())
+}
+
+/// Use a different data source from now on, e.g. fixtures for a demo.
+pub fn set_source(source: impl DataSource + 'static) {
+    SOURCE.with(|current| *current.borrow_mut() = Rc::new(source));
+}
+
+/// Pages through birds by id, so that large collections can be loaded bit by bit.
+pub struct BirdPages {
+    /// Sorted and deduplicated
+    ids: Vec<u64>,
+    page_size: usize,
+    offset: usize,
+}
+
+impl BirdPages {
+    pub fn new(mut ids: Vec<u64>, page_size: usize) -> Self {
+        ids.sort_unstable();
+        ids.dedup();
+        Self {
+            ids,
+            page_size: page_size.max(1),
+            offset: 0,
+        }
+    }
+
+    /// Fetch the next page, or `None` once all birds have been fetched. Failed pages can be
+    /// retried by calling this again.
+    pub async fn next_page(&mut self) -> Option<Result<Vec<Bird>>> {
+        if self.is_done() {
+            return None;
+        }
+        let end = (self.offset + self.page_size).min(self.ids.len());
+        let page = source()
+            .birds_by_ids(self.ids[self.offset..end].to_vec())
+            .await;
+        if page.is_ok() {
+            self.offset = end;
+        }
+        Some(page)
+    }
+
+    /// The total number of birds
+    pub fn total(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.offset >= self.ids.len()
+    }
+}
@@ -0,0 +1,232 @@
+//! The Supabase REST API, and how our models map onto its tables and views.
+
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use super::{DataSource, LocalBoxFuture};
+use crate::{
+    achievements::FamilyBirds,
+    bird::{Bird, BirdPack},
+    payments::Entitlement,
+    supabase::{
+        columns, Error, Is, Result, SupabaseRequest, SupabaseResource, DEFAULT_RETRIES, MAX_ROWS,
+    },
+    sync::UserStats,
+};
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SupabaseSource;
+
+impl DataSource for SupabaseSource {
+    fn birds_by_ids(&self, ids: Vec<u64>) -> LocalBoxFuture<'_, Result<Vec<Bird>>> {
+        Box::pin(async move {
+            let mut birds = Bird::request()
+                .select("*")
+                .in_(BirdColumn::Id, ids)
+                .order(BirdColumn::Id, true)
+                .pages(MAX_ROWS)
+                .collect_all()
+                .await?;
+            sign_media(&mut birds).await;
+            Ok(birds)
+        })
+    }
+
+    fn birds_of_families(&self, families: Vec<String>) -> LocalBoxFuture<'_, Result<FamilyBirds>> {
+        Box::pin(async move {
+            #[derive(Deserialize)]
+            struct FamilyBird {
+                id: u64,
+                family: String,
+            }
+
+            let mut birds = FamilyBirds::new();
+            if families.is_empty() {
+                return Ok(birds);
+            }
+            let rows = Bird::request()
+                .select(format!(
+                    "{},{}",
+                    BirdColumn::Id.as_ref(),
+                    BirdColumn::Family.as_ref()
+                ))
+                .in_(BirdColumn::Family, families)
+                .order(BirdColumn::Id, true)
+                .cast::<Vec<FamilyBird>>()
+                .pages(MAX_ROWS)
+                .collect_all()
+                .await?;
+            for row in rows {
+                birds.entry(row.family).or_default().push(row.id);
+            }
+            Ok(birds)
+        })
+    }
+
+    fn pack_by_id(&self, id: u64) -> LocalBoxFuture<'_, Result<BirdPack>> {
+        Box::pin(async move {
+            let mut pack = BirdPack::request()
+                .select("*")
+                .eq(BirdPackColumn::Id, id)
+                .execute()
+                .await?
+                .pop()
+                .ok_or(Error::NoPack(id))?;
+            sign_media(&mut pack.birds).await;
+            Ok(pack)
+        })
+    }
+
+    fn daily_pack(&self, day: NaiveDate) -> LocalBoxFuture<'_, Result<BirdPack>> {
+        Box::pin(async move {
+            let mut pack = BirdPack::request()
+                .select("*")
+                .eq(BirdPackColumn::Day, day.format("%Y-%m-%d"))
+                .execute()
+                .await?
+                .pop()
+                .ok_or(Error::NoDailyPack)?;
+            sign_media(&mut pack.birds).await;
+            Ok(pack)
+        })
+    }
+
+    fn packs(&self) -> LocalBoxFuture<'_, Result<Vec<BirdPack>>> {
+        Box::pin(async move {
+            BirdPack::request()
+                .select("*")
+                .is(BirdPackColumn::Day, Is::Null)
+                .order(BirdPackColumn::Id, true)
+                .execute()
+                .await
+        })
+    }
+
+    fn entitlements(&self, user_id: String) -> LocalBoxFuture<'_, Result<Vec<Entitlement>>> {
+        Box::pin(async move {
+            Entitlement::request()
+                .select("*")
+                .eq(EntitlementColumn::UserId, user_id)
+                .execute()
+                .await
+        })
+    }
+
+    fn load_stats(&self, user_id: String) -> LocalBoxFuture<'_, Result<Option<UserStats>>> {
+        Box::pin(async move {
+            let mut stats = UserStats::request()
+                .select("*")
+                .eq(UserStatsColumn::UserId, user_id)
+                .execute()
+                .await?;
+            Ok(stats.pop())
+        })
+    }
+
+    fn save_stats(&self, stats: UserStats) -> LocalBoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            SupabaseRequest::<()>::rpc("upsert_stats", &stats)?
+                // upserting the same stats twice is harmless
+                .retries(DEFAULT_RETRIES)
+                .execute()
+                .await
+        })
+    }
+}
+
+/// Sign the media URLs of birds, since their media isn't public. Users can only sign media of
+/// birds in packs they may play (free packs included), and the UI shows others that the pack is
+/// locked. Birds are still returned if their media can't be signed, e.g. offline, when the service
+/// worker serves what it cached of their media.
+async fn sign_media(birds: &mut [Bird]) {
+    if let Err(e) = Bird::sign_media(birds).await {
+        tracing::warn!("Failed to sign media of birds: {e}");
+    }
+}
+
+columns! {
+    pub enum BirdColumn {
+        Id => "id",
+        SpeciesCode => "species_code",
+        CommonName => "common_name",
+        CommonNames => "common_names",
+        ScientificName => "scientific_name",
+        Family => "family",
+        Image => "image",
+        ImageAttribution => "image_attribution",
+        Sounds => "sounds",
+    }
+}
+
+impl SupabaseResource for Bird {
+    type Column = BirdColumn;
+
+    fn table_name() -> &'static str {
+        "birds_detailed"
+    }
+
+    // Birds rarely change
+    fn cache_ttl() -> Option<Duration> {
+        Some(Duration::from_secs(24 * 60 * 60))
+    }
+}
+
+columns! {
+    pub enum BirdPackColumn {
+        Id => "id",
+        Name => "name",
+        Description => "description",
+        Birds => "birds",
+        Day => "day",
+        Free => "free",
+    }
+}
+
+impl SupabaseResource for BirdPack {
+    type Column = BirdPackColumn;
+
+    fn table_name() -> &'static str {
+        "bird_packs_detailed"
+    }
+
+    // Short enough to pick up a newly generated daily pack
+    fn cache_ttl() -> Option<Duration> {
+        Some(Duration::from_secs(60 * 60))
+    }
+}
+
+columns! {
+    pub enum UserStatsColumn {
+        UserId => "user_id",
+        Data => "data",
+        UpdatedAt => "updated_at",
+    }
+}
+
+impl SupabaseResource for UserStats {
+    type Column = UserStatsColumn;
+
+    fn table_name() -> &'static str {
+        "stats"
+    }
+}
+
+columns! {
+    pub enum EntitlementColumn {
+        UserId => "user_id",
+        Pack => "pack",
+        Provider => "provider",
+        Reference => "reference",
+        ExpiresAt => "expires_at",
+    }
+}
+
+impl SupabaseResource for Entitlement {
+    type Column = EntitlementColumn;
+
+    fn table_name() -> &'static str {
+        "entitlements"
+    }
+}
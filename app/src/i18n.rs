@@ -0,0 +1,176 @@
+//! Internationalization of the UI.
+//!
+//! UI strings live in per-locale [Fluent](https://projectfluent.org) resources under `locales/`,
+//! and are looked up by message id with the [`t!`] macro using the current [`LOCALE`].
+
+use std::collections::HashMap;
+
+use dioxus::prelude::*;
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use serde::{Deserialize, Serialize};
+use unic_langid::LanguageIdentifier;
+
+/// The locale used to render the UI.
+pub static LOCALE: GlobalSignal<Locale> = Signal::global(Locale::default);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Es];
+
+    /// The language code, e.g. "es"
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+        }
+    }
+
+    /// The name of the locale, in its own language
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Es => "Español",
+        }
+    }
+
+    /// Find the supported locale for a language tag, e.g. "es-MX"
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['-', '_']).next().unwrap_or(tag);
+        Self::ALL
+            .into_iter()
+            .find(|l| l.code().eq_ignore_ascii_case(language))
+    }
+
+    fn resource(&self) -> &'static str {
+        match self {
+            Locale::En => include_str!("../locales/en.ftl"),
+            Locale::Es => include_str!("../locales/es.ftl"),
+        }
+    }
+
+    fn bundle(&self) -> FluentBundle<FluentResource> {
+        let langid: LanguageIdentifier = self.code().parse().expect("valid language code");
+        let resource =
+            FluentResource::try_new(self.resource().to_string()).expect("valid fluent resource");
+        let mut bundle = FluentBundle::new(vec![langid]);
+        // Don't wrap arguments in unicode isolation marks
+        bundle.set_use_isolating(false);
+        bundle
+            .add_resource(resource)
+            .expect("no duplicate message ids");
+        bundle
+    }
+}
+
+thread_local! {
+    static BUNDLES: HashMap<Locale, FluentBundle<FluentResource>> =
+        Locale::ALL.into_iter().map(|l| (l, l.bundle())).collect();
+}
+
+/// Translate message `id`, falling back to english if `locale` is missing it.
+pub fn translate(locale: Locale, id: &str, args: Option<&FluentArgs>) -> String {
+    BUNDLES.with(|bundles| {
+        [locale, Locale::En]
+            .iter()
+            .find_map(|l| {
+                let bundle = &bundles[l];
+                let pattern = bundle.get_message(id)?.value()?;
+                let mut errors = vec![];
+                let value = bundle.format_pattern(pattern, args, &mut errors);
+                if !errors.is_empty() {
+                    tracing::warn!("Errors formatting message {id}: {errors:?}");
+                }
+                Some(value.into_owned())
+            })
+            .unwrap_or_else(|| {
+                tracing::error!("Missing translation for message {id}");
+                id.to_string()
+            })
+    })
+}
+
+/// Stands in for an element, e.g. a link, within a translated sentence, so that translators can
+/// place it wherever their language needs. See [`split_at_element`].
+pub const ELEMENT: &str = "\u{FFFC}";
+
+/// Split a message translated with [`ELEMENT`] as an argument into the text before and after it.
+pub fn split_at_element(message: &str) -> (&str, &str) {
+    message.split_once(ELEMENT).unwrap_or((message, ""))
+}
+
+/// Translate a message into the current [`LOCALE`], e.g. `t!("select-more-birds", count = 3)`.
+///
+/// Reading the locale subscribes the calling component, so it will rerender on locale changes.
+#[macro_export]
+macro_rules! t {
+    ($id:expr) => {
+        $crate::i18n::translate(*$crate::i18n::LOCALE.read(), $id, None)
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::translate(*$crate::i18n::LOCALE.read(), $id, Some(&args))
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locales_have_the_same_messages() {
+        let ids = |locale: Locale| {
+            let mut ids: Vec<&str> = locale
+                .resource()
+                .lines()
+                .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+                .filter_map(|line| line.split_once(" =").map(|(id, _)| id))
+                .collect();
+            ids.sort();
+            ids
+        };
+        for locale in Locale::ALL {
+            assert_eq!(
+                ids(locale),
+                ids(Locale::En),
+                "{locale:?} is missing messages"
+            );
+        }
+    }
+
+    #[test]
+    fn translates_with_plurals() {
+        let mut args = FluentArgs::new();
+        args.set("count", 1);
+        assert_eq!(
+            translate(Locale::En, "select-more-birds", Some(&args)),
+            "Select 1 more bird"
+        );
+        args.set("count", 3);
+        assert_eq!(
+            translate(Locale::Es, "select-more-birds", Some(&args)),
+            "Selecciona 3 aves más"
+        );
+    }
+
+    #[test]
+    fn splits_sentences_around_elements() {
+        let mut args = FluentArgs::new();
+        args.set("link", ELEMENT);
+        let message = translate(Locale::Es, "play-to-get-started", Some(&args));
+        assert_eq!(split_at_element(&message), ("Juega el ", " para empezar."));
+    }
+
+    #[test]
+    fn parses_language_tags() {
+        assert_eq!(Locale::from_tag("es-MX"), Some(Locale::Es));
+        assert_eq!(Locale::from_tag("en_US"), Some(Locale::En));
+        assert_eq!(Locale::from_tag("fr"), None);
+    }
+}
@@ -4,9 +4,16 @@
 use dioxus::prelude::*;
 use tracing::Level;
 
+mod achievements;
 mod bird;
+mod clock;
 mod conf;
+mod datasource;
+mod i18n;
+mod offline;
 mod pack;
+mod payments;
+mod server;
 mod stats;
 mod supabase;
 mod sync;
@@ -23,6 +30,23 @@ fn main() {
         .unwrap_or(Level::INFO);
     dioxus_logger::init(log_level).expect("failed to init logger");
 
-    // Launch the app
-    launch(ui::App)
+    // Load config, then launch the app
+    #[cfg(feature = "web")]
+    wasm_bindgen_futures::spawn_local(async {
+        match conf::init().await {
+            Ok(_) => launch(ui::App),
+            Err(e) => {
+                tracing::error!("Invalid configuration: {e}");
+                launch(ui::InvalidConfig)
+            }
+        }
+    });
+    #[cfg(not(feature = "web"))]
+    {
+        // e.g. when generating static pages, which don't fetch data
+        if let Err(e) = conf::init() {
+            tracing::warn!("Invalid configuration: {e}");
+        }
+        launch(ui::App)
+    }
 }
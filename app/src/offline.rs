@@ -0,0 +1,118 @@
+//! Offline support, so the game can be played in the field without a connection.
+//!
+//! The service worker in `assets/sw.js` caches pages, database reads and media as they're fetched.
+//! Here we register it, warm its caches ahead of time, and track connectivity so that stats can
+//! be synced once we're back online (see [`crate::sync`]).
+
+use dioxus::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    bird::{Bird, BirdPack, ImageSize},
+    clock::Clock,
+};
+
+/// Number of daily packs, starting today, to cache for offline play.
+const DAILY_PACKS_TO_CACHE: usize = 3;
+
+/// Whether the browser is online.
+pub static ONLINE: GlobalSignal<bool> = Signal::global(|| true);
+
+/// Messages understood by the service worker.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum WorkerMessage {
+    CacheMedia { urls: Vec<String> },
+}
+
+pub fn register_service_worker() {
+    #[cfg(feature = "web")]
+    if let Some(window) = web_sys::window() {
+        let registration = window.navigator().service_worker().register("/sw.js");
+        spawn(async move {
+            if let Err(e) = wasm_bindgen_futures::JsFuture::from(registration).await {
+                tracing::warn!("Failed to register service worker: {e:?}");
+            }
+        });
+    }
+}
+
+/// Keep [`ONLINE`] up to date with the browser's connectivity.
+pub async fn watch_connectivity() {
+    #[cfg(feature = "web")]
+    if let Some(window) = web_sys::window() {
+        use web_sys::js_sys::Promise;
+
+        loop {
+            *ONLINE.write() = window.navigator().on_line();
+            let change = Promise::new(&mut |resolve, _| {
+                window.set_ononline(Some(&resolve));
+                window.set_onoffline(Some(&resolve));
+            });
+            wasm_bindgen_futures::JsFuture::from(change).await.ok();
+        }
+    }
+}
+
+/// Fetch the upcoming daily packs and the given learned birds, so that the service worker caches
+/// them along with their images and sounds.
+pub async fn cache_for_offline(learned: Vec<u64>) {
+    let mut birds = Vec::new();
+    for day in Clock::System.today().iter_days().take(DAILY_PACKS_TO_CACHE) {
+        match BirdPack::fetch_by_day(day).await {
+            Ok(pack) => birds.extend(pack.birds),
+            // later packs might not be generated yet
+            Err(e) => tracing::debug!("Not caching daily pack for {day}: {e}"),
+        }
+    }
+    if !learned.is_empty() {
+        match Bird::fetch_by_ids(learned).await {
+            Ok(learned) => birds.extend(learned),
+            Err(e) => tracing::warn!("Failed to fetch learned birds for offline play: {e}"),
+        }
+    }
+
+    // images at the sizes they're shown at
+    let urls = birds
+        .iter()
+        .flat_map(|bird| {
+            [
+                bird.image_url_sized(ImageSize::Small),
+                bird.image_url_sized(ImageSize::Medium),
+                bird.default_sound_url(),
+            ]
+        })
+        .collect();
+    post_message(&WorkerMessage::CacheMedia { urls }).await;
+}
+
+async fn post_message(message: &WorkerMessage) {
+    #[cfg(feature = "web")]
+    if let Some(window) = web_sys::window() {
+        use wasm_bindgen::JsCast;
+        use web_sys::{js_sys::JSON, ServiceWorkerRegistration};
+
+        // wait for the service worker, which might still be installing on first load
+        let Ok(ready) = window.navigator().service_worker().ready() else {
+            return;
+        };
+        let worker = wasm_bindgen_futures::JsFuture::from(ready)
+            .await
+            .ok()
+            .and_then(|registration| {
+                registration
+                    .unchecked_into::<ServiceWorkerRegistration>()
+                    .active()
+            });
+        let Some(worker) = worker else {
+            tracing::debug!("No active service worker");
+            return;
+        };
+        let json = serde_json::to_string(message).expect("messages are serializable");
+        if let Err(e) = JSON::parse(&json).and_then(|message| worker.post_message(&message)) {
+            tracing::warn!("Failed to message service worker: {e:?}");
+        }
+    }
+    #[cfg(not(feature = "web"))]
+    let _ = message;
+}
@@ -1,9 +1,12 @@
 use std::fmt::{self, Display};
 
 use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     bird::{Bird, BirdPack},
+    clock::Clock,
+    stats::Stats,
     supabase::Result,
     utils,
 };
@@ -23,6 +26,8 @@ pub struct Pack {
     /// If this is not an ad-hoc list of birds, this is the id of the birdpack in the database. We
     /// store this so we can record stats per birdpack after completion.
     pub birdpack_id: Option<u64>,
+    /// Whether the pack can be played without paying, see [`crate::payments`]
+    pub free: bool,
 }
 
 impl PartialEq for Pack {
@@ -35,7 +40,7 @@ impl PartialEq for Pack {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PackIdentifier {
     Id(u64),
     Date(NaiveDate),
@@ -44,7 +49,7 @@ pub enum PackIdentifier {
 
 impl Default for PackIdentifier {
     fn default() -> Self {
-        PackIdentifier::Date(utils::today())
+        PackIdentifier::Date(Clock::System.today())
     }
 }
 
@@ -58,6 +63,7 @@ impl From<BirdPack> for Pack {
             id,
             birds: pack.birds,
             birdpack_id: Some(pack.id),
+            free: pack.free,
         }
     }
 }
@@ -69,6 +75,7 @@ impl From<Vec<Bird>> for Pack {
             id: PackIdentifier::Birds(bird_ids),
             birds,
             birdpack_id: None,
+            free: true,
         }
     }
 }
@@ -89,14 +96,37 @@ impl Pack {
                     .await
                     .map(|birds| Pack {
                         id: id.clone(),
-                        birds,
-                        birdpack_id: None,
+                        ..birds.into()
                     })
             }
         }
     }
 }
 
+impl PackIdentifier {
+    /// A review of the birds most often mistaken for one another, topped up with learned birds to
+    /// make `size` birds. `None` if no birds have been confused yet, or there aren't enough birds.
+    pub fn confused_review(stats: &Stats, size: usize) -> Option<Self> {
+        let pairs = stats.most_confused_pairs();
+        if pairs.is_empty() {
+            return None;
+        }
+        let mut learned = stats.birds_learned();
+        learned.sort();
+        let mut bird_ids = Vec::with_capacity(size);
+        let confused = pairs.iter().flat_map(|pair| [pair.birds.0, pair.birds.1]);
+        for bird_id in confused.chain(learned) {
+            if bird_ids.len() == size {
+                break;
+            }
+            if !bird_ids.contains(&bird_id) {
+                bird_ids.push(bird_id);
+            }
+        }
+        (bird_ids.len() == size).then_some(PackIdentifier::Birds(bird_ids))
+    }
+}
+
 const LIST_DELIM: char = '.';
 
 impl From<&str> for PackIdentifier {
@@ -0,0 +1,57 @@
+//! Payment links, i.e. hosted checkout pages that need no backend of our own (e.g. Stripe's).
+//! The provider's webhook grants the entitlement, matching the purchase to the user by the
+//! `client_reference_id` passed along here.
+
+use super::{Checkout, Customer, PaymentProvider, Product};
+use crate::{conf, datasource::LocalBoxFuture, supabase::Result, utils};
+
+/// Sends users to the configured `payment_link_url` to buy every pack.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PaymentLinkProvider;
+
+impl PaymentProvider for PaymentLinkProvider {
+    fn checkout(
+        &self,
+        customer: Customer,
+        product: Product,
+    ) -> LocalBoxFuture<'_, Result<Checkout>> {
+        Box::pin(async move {
+            let Some(link) = &conf::get().payment_link_url else {
+                return Err("No payment link is configured".into());
+            };
+            if product != Product::AllPacks {
+                return Err("Payment links only sell every pack".into());
+            }
+            Ok(Checkout::Redirect(checkout_url(link, &customer)))
+        })
+    }
+}
+
+fn checkout_url(link: &str, customer: &Customer) -> String {
+    let mut url = format!(
+        "{link}?client_reference_id={}",
+        utils::encode_uri_component(&customer.user_id)
+    );
+    if let Some(email) = &customer.email {
+        url.push_str("&prefilled_email=");
+        url.push_str(&utils::encode_uri_component(email));
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifies_the_customer() {
+        let customer = Customer {
+            user_id: "f3c1a2b4".to_string(),
+            email: Some("birder+1@example.com".to_string()),
+        };
+        assert_eq!(
+            checkout_url("https://buy.stripe.com/abc", &customer),
+            "https://buy.stripe.com/abc?client_reference_id=f3c1a2b4&prefilled_email=birder%2B1%40example.com"
+        );
+    }
+}
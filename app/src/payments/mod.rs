@@ -0,0 +1,224 @@
+//! Premium packs, i.e. those that aren't [free](BirdPack::free), and paying for them.
+//!
+//! What users have paid for is recorded as [`Entitlement`]s, which the payment provider grants
+//! (e.g. from a webhook) and the app reads through its [`DataSource`](crate::datasource). Checkout
+//! goes through the current [`PaymentProvider`], which is a payment link by default, or a fake
+//! that grants entitlements right away with the `fixtures` feature (e.g. for demos and UI tests).
+
+pub mod fake;
+pub mod link;
+
+use std::{cell::RefCell, rc::Rc};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bird::BirdPack,
+    datasource::{self, LocalBoxFuture},
+    pack::Pack,
+    supabase::Result,
+};
+
+/// A user's right to play a pack, or every pack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entitlement {
+    pub user_id: String,
+    /// `None` for every pack, e.g. with a subscription
+    pub pack: Option<u64>,
+    /// Which payment provider granted it
+    pub provider: String,
+    /// The provider's id for the purchase
+    pub reference: String,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl Entitlement {
+    pub fn covers(&self, pack_id: u64) -> bool {
+        self.pack.unwrap_or(pack_id) == pack_id && self.is_active()
+    }
+
+    pub fn is_active(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => Utc::now() < expires_at,
+            None => true,
+        }
+    }
+}
+
+/// Everything a user is entitled to.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Entitlements(Vec<Entitlement>);
+
+impl Entitlements {
+    pub fn new(entitlements: Vec<Entitlement>) -> Self {
+        Self(entitlements)
+    }
+
+    /// Fetch the user's entitlements
+    pub async fn fetch(user_id: String) -> Result<Self> {
+        datasource::source().entitlements(user_id).await.map(Self)
+    }
+
+    pub fn grant(&mut self, entitlement: Entitlement) {
+        self.0.push(entitlement);
+    }
+
+    /// Whether the user may play a pack from the database.
+    pub fn unlocks(&self, pack: &BirdPack) -> bool {
+        pack.free || self.covers(pack.id)
+    }
+
+    /// Whether the user may play a pack. The app only makes ad-hoc packs of birds already
+    /// learned, so they're always unlocked here, but the server checks their birds with
+    /// [`Entitlements::unlocks_birds`], since anyone can ask it for any birds.
+    pub fn unlocks_pack(&self, pack: &Pack) -> bool {
+        match pack.birdpack_id {
+            Some(id) => pack.free || self.covers(id),
+            None => true,
+        }
+    }
+
+    /// Whether the user may play each of `bird_ids`, i.e. in an ad-hoc pack: birds they've
+    /// `learned`, or that are in a pack they may play. `packs_of_birds` lists the packs each bird
+    /// is in.
+    pub fn unlocks_birds(
+        &self,
+        bird_ids: &[u64],
+        learned: &[u64],
+        packs_of_birds: &[BirdInPack],
+    ) -> bool {
+        bird_ids.iter().all(|id| {
+            learned.contains(id)
+                || packs_of_birds
+                    .iter()
+                    .any(|p| p.bird == *id && (p.free || self.covers(p.pack)))
+        })
+    }
+
+    /// Whether the user has paid for every pack
+    pub fn is_premium(&self) -> bool {
+        self.0.iter().any(|e| e.pack.is_none() && e.is_active())
+    }
+
+    fn covers(&self, pack_id: u64) -> bool {
+        self.0.iter().any(|e| e.covers(pack_id))
+    }
+}
+
+/// A pack a bird is in, and whether that pack is free.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BirdInPack {
+    pub bird: u64,
+    pub pack: u64,
+    pub free: bool,
+}
+
+/// What can be bought
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Product {
+    Pack(u64),
+    AllPacks,
+}
+
+/// Who is buying
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Customer {
+    pub user_id: String,
+    pub email: Option<String>,
+}
+
+/// Where a checkout got to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Checkout {
+    /// The user should continue at this URL, e.g. a hosted checkout page. The entitlement is
+    /// granted once they've paid, so it should be fetched again when they come back.
+    Redirect(String),
+    /// Paid for already
+    Granted(Entitlement),
+}
+
+pub trait PaymentProvider {
+    fn checkout(
+        &self,
+        customer: Customer,
+        product: Product,
+    ) -> LocalBoxFuture<'_, Result<Checkout>>;
+}
+
+thread_local! {
+    static PROVIDER: RefCell<Rc<dyn PaymentProvider>> = RefCell::new(default_provider());
+}
+
+fn default_provider() -> Rc<dyn PaymentProvider> {
+    #[cfg(feature = "fixtures")]
+    {
+        Rc::new(fake::FakeProvider)
+    }
+    #[cfg(not(feature = "fixtures"))]
+    {
+        Rc::new(link::PaymentLinkProvider)
+    }
+}
+
+/// The payment provider in use.
+pub fn provider() -> Rc<dyn PaymentProvider> {
+    PROVIDER.with(|provider| provider.borrow().clone())
+}
+
+/// Use a different payment provider from now on, e.g. a fake one in tests.
+pub fn set_provider(provider: impl PaymentProvider + 'static) {
+    PROVIDER.with(|current| *current.borrow_mut() = Rc::new(provider));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entitlement(pack: Option<u64>, expires_at: Option<DateTime<Utc>>) -> Entitlement {
+        Entitlement {
+            user_id: "user".to_string(),
+            pack,
+            provider: "test".to_string(),
+            reference: "ref".to_string(),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn unlocks_entitled_packs() {
+        let hour = chrono::Duration::hours(1);
+        let entitlements = Entitlements::new(vec![
+            entitlement(Some(1), None),
+            entitlement(Some(2), Some(Utc::now() - hour)),
+            entitlement(Some(3), Some(Utc::now() + hour)),
+        ]);
+        assert!(entitlements.covers(1));
+        assert!(!entitlements.covers(2));
+        assert!(entitlements.covers(3));
+        assert!(!entitlements.covers(4));
+        assert!(!entitlements.is_premium());
+
+        let premium = Entitlements::new(vec![entitlement(None, None)]);
+        assert!(premium.covers(4));
+        assert!(premium.is_premium());
+    }
+
+    #[test]
+    fn unlocks_learned_birds_and_birds_of_unlocked_packs() {
+        let in_pack = |bird, pack, free| BirdInPack { bird, pack, free };
+        // bird 1 is in a free pack, 2 in pack 5 only, 3 in pack 6 only, 4 in no pack
+        let packs_of_birds = [
+            in_pack(1, 4, true),
+            in_pack(2, 5, false),
+            in_pack(3, 6, false),
+        ];
+        let entitlements = Entitlements::new(vec![entitlement(Some(5), None)]);
+        assert!(entitlements.unlocks_birds(&[1, 2], &[], &packs_of_birds));
+        assert!(!entitlements.unlocks_birds(&[1, 3], &[], &packs_of_birds));
+        assert!(!entitlements.unlocks_birds(&[4], &[], &packs_of_birds));
+        assert!(entitlements.unlocks_birds(&[1, 3, 4], &[3, 4], &packs_of_birds));
+        assert!(Entitlements::default().unlocks_birds(&[], &[], &[]));
+    }
+}
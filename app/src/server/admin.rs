@@ -0,0 +1,245 @@
+//! Supabase requests made by the server with the service role key, which bypasses row level
+//! security. They must only ever be made on behalf of a user whose token has been verified.
+
+use chrono::Utc;
+use dioxus::prelude::ServerFnError;
+use once_cell::sync::Lazy;
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    achievements::FamilyBirds,
+    bird::{Bird, BirdPack},
+    conf,
+    pack::{Pack, PackIdentifier},
+    payments::{BirdInPack, Entitlement, Entitlements},
+    server::GameRecord,
+    stats::{Answer, Stats},
+    supabase::DEFAULT_TIMEOUT,
+    utils,
+};
+
+static CLIENT: Lazy<Client> = Lazy::new(|| {
+    Client::builder()
+        .timeout(DEFAULT_TIMEOUT)
+        .build()
+        .expect("failed to build HTTP client")
+});
+
+/// The service role key, which is a secret, so it's only ever read from the server's environment.
+pub fn service_role_key() -> Result<String, ServerFnError> {
+    std::env::var("SUPABASE_SERVICE_ROLE_KEY")
+        .ok()
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| ServerFnError::new("Missing SUPABASE_SERVICE_ROLE_KEY"))
+}
+
+/// The id of the user the access token belongs to, if it's valid.
+pub async fn user_id(access_token: &str) -> Result<String, ServerFnError> {
+    #[derive(Deserialize)]
+    struct User {
+        id: String,
+    }
+
+    let config = conf::get();
+    let rsp = CLIENT
+        .get(format!("{}/auth/v1/user", config.supabase_api_url))
+        .header("apikey", &config.supabase_anon_key)
+        .bearer_auth(access_token)
+        .send()
+        .await?;
+    if !rsp.status().is_success() {
+        return Err(ServerFnError::new("Invalid or expired session"));
+    }
+    Ok(rsp.json::<User>().await?.id)
+}
+
+/// The pack as the client would have played it, see [`Pack::fetch_by_id`].
+pub async fn fetch_pack(id: &PackIdentifier) -> Result<Pack, ServerFnError> {
+    match id {
+        PackIdentifier::Id(pack_id) => {
+            let pack = fetch_bird_pack("id", pack_id).await?;
+            Ok(Pack {
+                id: id.clone(),
+                ..pack.into()
+            })
+        }
+        PackIdentifier::Date(day) => {
+            let pack = fetch_bird_pack("day", day).await?;
+            Ok(Pack {
+                id: id.clone(),
+                ..pack.into()
+            })
+        }
+        PackIdentifier::Birds(bird_ids) => {
+            let ids = format!("in.({})", utils::join(bird_ids, ","));
+            let birds: Vec<Bird> = select("birds_detailed", &[("id", ids)]).await?;
+            if birds.len() < bird_ids.len() {
+                return Err(ServerFnError::new(format!("Unknown birds in pack {id}")));
+            }
+            Ok(Pack {
+                id: id.clone(),
+                ..birds.into()
+            })
+        }
+    }
+}
+
+async fn fetch_bird_pack(
+    column: &str,
+    value: impl std::fmt::Display,
+) -> Result<BirdPack, ServerFnError> {
+    let packs: Vec<BirdPack> =
+        select("bird_packs_detailed", &[(column, format!("eq.{value}"))]).await?;
+    packs
+        .into_iter()
+        .next()
+        .ok_or_else(|| ServerFnError::new(format!("No pack with {column} {value}")))
+}
+
+pub async fn entitlements(user_id: &str) -> Result<Entitlements, ServerFnError> {
+    let entitlements: Vec<Entitlement> =
+        select("entitlements", &[("user_id", format!("eq.{user_id}"))]).await?;
+    Ok(Entitlements::new(entitlements))
+}
+
+/// Which packs each of the birds is in, and whether those packs are free.
+pub async fn packs_of_birds(bird_ids: &[u64]) -> Result<Vec<BirdInPack>, ServerFnError> {
+    #[derive(Deserialize)]
+    struct Row {
+        bird: u64,
+        pack: u64,
+        packs: PackRow,
+    }
+    #[derive(Deserialize)]
+    struct PackRow {
+        free: bool,
+    }
+
+    let ids = format!("in.({})", utils::join(bird_ids, ","));
+    let rows: Vec<Row> =
+        select_columns("bird_pack", "bird,pack,packs(free)", &[("bird", ids)]).await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| BirdInPack {
+            bird: row.bird,
+            pack: row.pack,
+            free: row.packs.free,
+        })
+        .collect())
+}
+
+/// Ids of every bird of the given families, by family.
+pub async fn birds_of_families(families: &[&str]) -> Result<FamilyBirds, ServerFnError> {
+    #[derive(Deserialize)]
+    struct Row {
+        id: u64,
+        family: String,
+    }
+
+    let mut birds = FamilyBirds::new();
+    if families.is_empty() {
+        return Ok(birds);
+    }
+    let quoted = families.iter().map(|family| format!("\"{family}\""));
+    let families = format!("in.({})", utils::join(quoted, ","));
+    let rows: Vec<Row> = select_columns("birds", "id,family", &[("family", families)]).await?;
+    for row in rows {
+        birds.entry(row.family).or_default().push(row.id);
+    }
+    Ok(birds)
+}
+
+#[derive(Deserialize)]
+struct VerifiedStats {
+    data: Stats,
+    version: u64,
+}
+
+/// The stats the server has recorded for the user, and their version, which is 0 if there are
+/// none yet (see [`record_game`]).
+pub async fn load_stats(user_id: &str) -> Result<(Stats, u64), ServerFnError> {
+    let stats: Vec<VerifiedStats> =
+        select("verified_stats", &[("user_id", format!("eq.{user_id}"))]).await?;
+    Ok(stats
+        .into_iter()
+        .next()
+        .map_or_else(Default::default, |s| (s.data, s.version)))
+}
+
+#[derive(Serialize)]
+struct RecordGame<'a> {
+    user_id: &'a str,
+    nonce: u64,
+    signature: &'a str,
+    pack: String,
+    started_at: chrono::DateTime<Utc>,
+    answers: &'a [Answer],
+    stats: &'a Stats,
+    xp: u32,
+    level: u32,
+    version: u64,
+}
+
+/// Record a game that's been validated, along with the stats it was applied to and the user's
+/// standing on the leaderboard, all at once. `version` is that of the stats the game was applied
+/// to: if they've changed since (e.g. another game was recorded meanwhile), nothing is recorded
+/// and this returns false. Fails if the game has been recorded before, so that each game can only
+/// count once.
+pub async fn record_game(
+    game: &GameRecord,
+    stats: &Stats,
+    version: u64,
+) -> Result<bool, ServerFnError> {
+    let session = &game.session;
+    let params = RecordGame {
+        user_id: &session.user_id,
+        nonce: session.nonce,
+        signature: &session.signature,
+        pack: session.pack.to_string(),
+        started_at: session.started_at,
+        answers: &game.answers,
+        stats,
+        xp: stats.xp(),
+        level: stats.level(),
+        version,
+    };
+    let rsp = request(Method::POST, "rpc/record_game")?
+        .json(&params)
+        .send()
+        .await?;
+    if rsp.status() == StatusCode::CONFLICT {
+        return Err(ServerFnError::new("Game was already recorded"));
+    }
+    Ok(rsp.error_for_status()?.json().await?)
+}
+
+fn request(method: Method, table: &str) -> Result<RequestBuilder, ServerFnError> {
+    let key = service_role_key()?;
+    let url = format!("{}/rest/v1/{table}", conf::get().supabase_api_url);
+    Ok(CLIENT
+        .request(method, url)
+        .header("apikey", &key)
+        .bearer_auth(&key))
+}
+
+async fn select<R: DeserializeOwned>(
+    table: &str,
+    filters: &[(&str, String)],
+) -> Result<Vec<R>, ServerFnError> {
+    select_columns(table, "*", filters).await
+}
+
+async fn select_columns<R: DeserializeOwned>(
+    table: &str,
+    columns: &str,
+    filters: &[(&str, String)],
+) -> Result<Vec<R>, ServerFnError> {
+    let rsp = request(Method::GET, table)?
+        .query(&[("select", columns)])
+        .query(filters)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(rsp.json().await?)
+}
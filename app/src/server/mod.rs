@@ -0,0 +1,508 @@
+//! Server functions, for operations that shouldn't trust the client.
+//!
+//! Stats synced from the browser (see [`crate::sync`]) are whatever is in local storage, so
+//! anything that should be trustworthy, like awarding XP and the leaderboard, is derived by the
+//! server instead. Before a game, the server signs a [`GameSession`] saying who started it, with
+//! which pack and when. Once it's finished, the client sends every answer along with the session,
+//! and the server replays them, rejecting games that couldn't have been played (e.g. faster than
+//! recordings can be listened to) before applying them to the user's verified stats. This needs
+//! the app to be served with the `fullstack` feature; static builds only sync stats.
+
+#[cfg(feature = "server")]
+mod admin;
+#[cfg(feature = "server")]
+mod session;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+#[cfg(feature = "fullstack")]
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    achievements::FamilyBirds,
+    bird::Bird,
+    pack::{Pack, PackIdentifier},
+    stats::{Answer, Stats, LEARN_THRESHOLD},
+    ui::game::quiz::MULTIPLE_CHOICE_SIZE,
+};
+
+/// Quicker than anyone could listen to a recording and pick out the bird, unless the recording is
+/// shorter still, see [`min_challenge_time`].
+pub const MIN_CHALLENGE_TIME: Duration = Duration::seconds(1);
+
+/// How long after starting a game it can be recorded, e.g. after being played offline.
+pub const SESSION_TTL: Duration = Duration::days(7);
+
+/// How far answers may be dated after the game was received, once they're moved onto the server's
+/// clock. The client starts the game while waiting for its session, so its answers seem later by as
+/// long as that took.
+const MAX_CLOCK_SKEW: Duration = Duration::minutes(1);
+
+/// A game the server has agreed to record, signed so that the client can't change it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameSession {
+    pub user_id: String,
+    pub pack: PackIdentifier,
+    /// According to the server
+    pub started_at: DateTime<Utc>,
+    /// Makes every session unique, so that each can only be recorded once
+    nonce: u64,
+    signature: String,
+}
+
+/// A game played to completion, as sent to the server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub session: GameSession,
+    /// Every answer, in the order they were given
+    pub answers: Vec<Answer>,
+    /// Whether it was a past daily pack, played from the archive, which doesn't count toward
+    /// streaks. Only the client knows, but it only stands to lose by saying so.
+    #[serde(default)]
+    pub from_archive: bool,
+}
+
+impl GameRecord {
+    /// Apply the game to `stats` the same way the client did while it was played, provided it's a
+    /// game that could have been completed with `pack` by the time it was received. Otherwise
+    /// `stats` is left as is. `families` are the birds of the families in the pack, to unlock
+    /// achievements with (see [`Stats::unlock_achievements`]).
+    pub fn apply(
+        &self,
+        pack: &Pack,
+        families: &FamilyBirds,
+        received_at: DateTime<Utc>,
+        stats: &mut Stats,
+    ) -> Result<(), String> {
+        let answers = self.validate_timing(pack, received_at)?;
+
+        // per bird, how much its consecutive identifications count toward learning it
+        let mut consecutive: HashMap<u64, u32> = pack.birds.iter().map(|b| (b.id, 0)).collect();
+        for answer in &self.answers {
+            if answer.pack != self.session.pack {
+                return Err(format!(
+                    "Answer for pack {} in a game of pack {}",
+                    answer.pack, self.session.pack
+                ));
+            }
+            if !consecutive.contains_key(&answer.chosen) {
+                return Err(format!(
+                    "Bird {} chosen isn't in pack {}",
+                    answer.chosen, self.session.pack
+                ));
+            }
+            let count = consecutive.get_mut(&answer.bird_id).ok_or_else(|| {
+                format!(
+                    "Bird {} isn't in pack {}",
+                    answer.bird_id, self.session.pack
+                )
+            })?;
+            *count = if answer.is_correct() {
+                *count + answer.learn_credit()
+            } else {
+                0
+            };
+        }
+        if consecutive.values().any(|count| *count < LEARN_THRESHOLD) {
+            return Err(format!("Pack {} wasn't completed", self.session.pack));
+        }
+
+        for answer in answers {
+            stats.record_answer(answer);
+        }
+        stats.record_pack_completed(pack, received_at, self.from_archive);
+        stats.unlock_achievements(pack, families, received_at);
+        Ok(())
+    }
+
+    /// Check that answers were given one challenge at a time, as the game asks them: each
+    /// challenge is a bird that's answered correctly, after at most one mistake per other choice,
+    /// and not before its recording could be heard (see [`min_challenge_time`]) since the previous
+    /// one. Response times can't be checked exactly, since the client decides when the recording
+    /// started, but they must fit between the two.
+    ///
+    /// The client's clock may well be set differently from the server's, so answers are moved onto
+    /// the server's clock as if the game started when the server started it, and returned that way
+    /// to be recorded. They must all be from between then and when the game was received, which
+    /// must have left enough time to hear every challenge.
+    fn validate_timing(
+        &self,
+        pack: &Pack,
+        received_at: DateTime<Utc>,
+    ) -> Result<Vec<Answer>, String> {
+        let started_at = self.session.started_at;
+        if received_at - started_at > SESSION_TTL {
+            return Err(format!("Game started at {started_at} has expired"));
+        }
+        let Some(client_started_at) = self.answers.first().map(|a| a.game_started_at) else {
+            return Ok(Vec::new());
+        };
+        let offset = started_at - client_started_at;
+        let answers: Vec<Answer> = self
+            .answers
+            .iter()
+            .map(|answer| Answer {
+                at: answer.at + offset,
+                // the only start of the game that can be trusted
+                game_started_at: started_at,
+                ..answer.clone()
+            })
+            .collect();
+        let min_challenge_times: HashMap<u64, Duration> = pack
+            .birds
+            .iter()
+            .map(|bird| (bird.id, min_challenge_time(bird)))
+            .collect();
+        let min_time = |bird_id: u64| {
+            min_challenge_times
+                .get(&bird_id)
+                .copied()
+                .unwrap_or(MIN_CHALLENGE_TIME)
+        };
+        // every challenge ends with a correct answer
+        let min_game_time = answers
+            .iter()
+            .filter(|answer| answer.is_correct())
+            .map(|answer| min_time(answer.bird_id))
+            .fold(Duration::zero(), |total, time| total + time);
+        if min_game_time > received_at - started_at {
+            return Err(format!(
+                "Game started at {started_at} was received too soon to have been played"
+            ));
+        }
+        let mut challenge_started_at = started_at;
+        let mut previous_at = started_at;
+        let mut mistakes: Vec<&Answer> = Vec::new();
+        for (answer, original) in answers.iter().zip(&self.answers) {
+            if original.game_started_at != client_started_at {
+                return Err(format!("Answer at {} is from another game", original.at));
+            }
+            if answer.at < previous_at {
+                return Err(format!("Answer at {} is out of order", original.at));
+            }
+            if answer.at > received_at + MAX_CLOCK_SKEW {
+                return Err(format!(
+                    "Answer at {} is later than the game was received",
+                    original.at
+                ));
+            }
+            let response_time = Duration::milliseconds(answer.response_time_ms.into());
+            let min_time = min_time(answer.bird_id);
+            if mistakes.is_empty()
+                && (answer.at - challenge_started_at < min_time || response_time < min_time)
+            {
+                return Err(format!("Bird {} was answered too quickly", answer.bird_id));
+            }
+            if response_time > answer.at - challenge_started_at {
+                return Err(format!(
+                    "Bird {} took longer to answer than its challenge",
+                    answer.bird_id
+                ));
+            }
+            if mistakes.iter().any(|m| m.bird_id != answer.bird_id) {
+                return Err(format!(
+                    "Bird {} was answered mid-challenge",
+                    answer.bird_id
+                ));
+            }
+            if answer.is_correct() {
+                challenge_started_at = answer.at;
+                mistakes.clear();
+            } else if mistakes.len() + 1 >= MULTIPLE_CHOICE_SIZE {
+                return Err(format!("Bird {} was mistaken too often", answer.bird_id));
+            } else {
+                mistakes.push(answer);
+            }
+            previous_at = answer.at;
+        }
+        Ok(answers)
+    }
+}
+
+/// How long it takes at least to answer a challenge for `bird`: to hear the whole recording the
+/// game plays, or the first [`MIN_CHALLENGE_TIME`] of longer ones, which is plenty to recognize
+/// a bird by.
+fn min_challenge_time(bird: &Bird) -> Duration {
+    bird.sounds
+        .iter()
+        .find(|s| s.default_)
+        .or(bird.sounds.first())
+        .and_then(|sound| sound.duration_ms)
+        .map_or(MIN_CHALLENGE_TIME, |ms| {
+            Duration::milliseconds(ms.into()).min(MIN_CHALLENGE_TIME)
+        })
+}
+
+/// Start a game of `pack`, so that it can be recorded once it's finished. `access_token` is the
+/// user's Supabase session, which the server verifies before starting anything for them.
+#[cfg(feature = "fullstack")]
+#[server]
+pub async fn start_game(
+    access_token: String,
+    pack: PackIdentifier,
+) -> Result<GameSession, ServerFnError> {
+    let user_id = admin::user_id(&access_token).await?;
+    let bird_pack = admin::fetch_pack(&pack).await?;
+    let entitlements = admin::entitlements(&user_id).await?;
+    let unlocked = match &pack {
+        PackIdentifier::Birds(bird_ids) => {
+            let (stats, _) = admin::load_stats(&user_id).await?;
+            let learned = stats.birds_learned();
+            let packs_of_birds = admin::packs_of_birds(bird_ids).await?;
+            entitlements.unlocks_birds(bird_ids, &learned, &packs_of_birds)
+        }
+        _ => entitlements.unlocks_pack(&bird_pack),
+    };
+    if !unlocked {
+        return Err(ServerFnError::new(format!("Pack {pack} is locked")));
+    }
+    let key = admin::service_role_key()?;
+    Ok(session::start(user_id, pack, Utc::now(), key.as_bytes()))
+}
+
+/// Record a game the user finished, returning their verified stats.
+#[cfg(feature = "fullstack")]
+#[server]
+pub async fn record_game(access_token: String, game: GameRecord) -> Result<Stats, ServerFnError> {
+    let received_at = Utc::now();
+    let user_id = admin::user_id(&access_token).await?;
+    let key = admin::service_role_key()?;
+    if game.session.user_id != user_id || !session::verify(&game.session, key.as_bytes()) {
+        return Err(ServerFnError::new("Invalid game session"));
+    }
+    let pack = admin::fetch_pack(&game.session.pack).await?;
+    let families =
+        admin::birds_of_families(&crate::achievements::Achievement::families_in(&pack)).await?;
+    // if another game is recorded at the same time, apply this one again to the stats it made
+    const MAX_ATTEMPTS: u32 = 3;
+    for _ in 0..MAX_ATTEMPTS {
+        let (mut stats, version) = admin::load_stats(&user_id).await?;
+        game.apply(&pack, &families, received_at, &mut stats)
+            .map_err(ServerFnError::new)?;
+        if admin::record_game(&game, &stats, version).await? {
+            return Ok(stats);
+        }
+    }
+    Err(ServerFnError::new(
+        "Stats kept changing while recording the game",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bird::Sound, stats::Mode};
+
+    fn pack(ids: &[u64]) -> Pack {
+        Pack::from(ids.iter().copied().map(Bird::fixture).collect::<Vec<_>>())
+    }
+
+    fn game(pack: &Pack, started_at: DateTime<Utc>, answers: &[(u64, bool, i64)]) -> GameRecord {
+        let session = GameSession {
+            user_id: "user".to_string(),
+            pack: pack.id.clone(),
+            started_at,
+            nonce: 0,
+            signature: String::new(),
+        };
+        // answers are timed from the end of the previous challenge
+        let mut challenge_started_at = 0;
+        let answers = answers
+            .iter()
+            .map(|(bird_id, correct, secs)| {
+                let answer = Answer {
+                    at: started_at + Duration::seconds(*secs),
+                    game_started_at: started_at,
+                    bird_id: *bird_id,
+                    pack: pack.id.clone(),
+                    mode: Mode::MultipleChoice,
+                    response_time_ms: ((secs - challenge_started_at) * 1000) as u32,
+                    // the other bird of the pack
+                    chosen: if *correct { *bird_id } else { bird_id % 2 + 1 },
+                };
+                if *correct {
+                    challenge_started_at = *secs;
+                }
+                answer
+            })
+            .collect();
+        GameRecord {
+            session,
+            answers,
+            from_archive: false,
+        }
+    }
+
+    #[test]
+    fn applies_only_completed_games() {
+        let pack = pack(&[1, 2]);
+        let start = Utc::now() - Duration::minutes(5);
+        let mut answers = vec![
+            (1, true, 2),
+            (2, false, 4),
+            (2, true, 5),
+            (1, true, 7),
+            (2, true, 9),
+            (1, true, 11),
+        ];
+
+        let mut stats = Stats::default();
+        let incomplete = game(&pack, start, &answers);
+        assert!(incomplete
+            .apply(&pack, &FamilyBirds::new(), Utc::now(), &mut stats)
+            .is_err());
+        assert_eq!(stats, Stats::default());
+
+        answers.push((2, true, 13));
+        game(&pack, start, &answers)
+            .apply(&pack, &FamilyBirds::new(), Utc::now(), &mut stats)
+            .unwrap();
+        assert_eq!(stats.total_birds_learned(), 2);
+        assert_eq!(stats.xp(), 2 * 10 + 6);
+
+        answers.push((3, true, 15));
+        let unknown_bird = game(&pack, start, &answers);
+        assert!(unknown_bird
+            .apply(&pack, &FamilyBirds::new(), Utc::now(), &mut stats)
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_impossible_timing() {
+        let pack = pack(&[1]);
+        let start = Utc::now() - Duration::minutes(5);
+        let now = Utc::now();
+        let valid = [(1, false, 2), (1, false, 2), (1, true, 3), (1, true, 5)];
+        assert!(game(&pack, start, &valid)
+            .validate_timing(&pack, now)
+            .is_ok());
+
+        let too_quick = [(1, true, 2), (1, true, 2)];
+        assert!(game(&pack, start, &too_quick)
+            .validate_timing(&pack, now)
+            .is_err());
+        let out_of_order = [(1, true, 4), (1, true, 2)];
+        assert!(game(&pack, start, &out_of_order)
+            .validate_timing(&pack, now)
+            .is_err());
+        let mut claimed_too_quick = game(&pack, start, &valid);
+        claimed_too_quick.answers[3].response_time_ms = 500;
+        assert!(claimed_too_quick.validate_timing(&pack, now).is_err());
+        let in_the_future = [(1, true, 10 * 60)];
+        assert!(game(&pack, start, &in_the_future)
+            .validate_timing(&pack, now)
+            .is_err());
+        let too_many_mistakes = [(1, false, 2), (1, false, 2), (1, false, 2), (1, false, 2)];
+        assert!(game(&pack, start, &too_many_mistakes)
+            .validate_timing(&pack, now)
+            .is_err());
+        let switched_birds = [(1, false, 2), (2, true, 3)];
+        assert!(game(&pack, start, &switched_birds)
+            .validate_timing(&pack, now)
+            .is_err());
+        let expired = game(&pack, now - SESSION_TTL - Duration::hours(1), &valid);
+        assert!(expired.validate_timing(&pack, now).is_err());
+    }
+
+    #[test]
+    fn rejects_games_received_before_they_could_be_played() {
+        let pack = pack(&[1]);
+        let start = Utc::now();
+        let valid = [(1, false, 2), (1, true, 3), (1, true, 5)];
+        let made_up = game(&pack, start, &valid);
+        assert!(made_up
+            .validate_timing(&pack, start + Duration::milliseconds(100))
+            .is_err());
+        // even if the client claims to have started long before the server did
+        let made_up = skew_clock(made_up, -Duration::seconds(30));
+        assert!(made_up
+            .validate_timing(&pack, start + Duration::seconds(1))
+            .is_err());
+        assert!(made_up
+            .validate_timing(&pack, start + Duration::seconds(5))
+            .is_ok());
+    }
+
+    #[test]
+    fn records_answers_on_the_servers_clock() {
+        let pack = pack(&[1, 2]);
+        let start = Utc::now() - Duration::minutes(5);
+        let now = Utc::now();
+        let answers = [
+            (1, true, 2),
+            (2, true, 4),
+            (1, true, 6),
+            (2, true, 8),
+            (1, true, 10),
+            (2, true, 12),
+        ];
+        let mut stats = Stats::default();
+        skew_clock(game(&pack, start, &answers), Duration::days(3))
+            .apply(&pack, &FamilyBirds::new(), now, &mut stats)
+            .unwrap();
+        let logged: Vec<DateTime<Utc>> = stats.log().iter().map(|e| e.at()).collect();
+        assert_eq!(logged[0], start + Duration::seconds(2));
+        assert_eq!(logged[5], start + Duration::seconds(12));
+        assert_eq!(logged[6], now);
+        assert_eq!(stats.game_answers(start).len(), 6);
+    }
+
+    /// Shift the client's clock, which answers are timed by, by `skew` from the server's.
+    fn skew_clock(mut game: GameRecord, skew: Duration) -> GameRecord {
+        for answer in &mut game.answers {
+            answer.at += skew;
+            answer.game_started_at += skew;
+        }
+        game
+    }
+
+    #[test]
+    fn times_answers_by_the_clients_clock() {
+        let pack = pack(&[1]);
+        let start = Utc::now() - Duration::minutes(5);
+        let now = Utc::now();
+        let valid = [(1, false, 2), (1, true, 3), (1, true, 5)];
+        for skew in [
+            Duration::hours(-3),
+            Duration::minutes(-2),
+            Duration::hours(2),
+        ] {
+            let game = skew_clock(game(&pack, start, &valid), skew);
+            assert!(game.validate_timing(&pack, now).is_ok(), "{skew}");
+        }
+
+        // but the game can't have taken longer than the server's had it
+        let too_long = skew_clock(
+            game(&pack, start, &[(1, true, 10 * 60)]),
+            -Duration::hours(1),
+        );
+        assert!(too_long.validate_timing(&pack, now).is_err());
+        let mut other_game = skew_clock(game(&pack, start, &valid), -Duration::hours(1));
+        other_game.answers[2].game_started_at -= Duration::minutes(1);
+        assert!(other_game.validate_timing(&pack, now).is_err());
+    }
+
+    #[test]
+    fn allows_answers_as_soon_as_short_recordings_are_heard() {
+        let mut pack = pack(&[1]);
+        let start = Utc::now() - Duration::minutes(5);
+        let now = Utc::now();
+        let mut quick = game(&pack, start, &[(1, true, 1)]);
+        quick.answers[0].at -= Duration::milliseconds(400);
+        quick.answers[0].response_time_ms = 600;
+        assert!(quick.validate_timing(&pack, now).is_err());
+
+        pack.birds[0].sounds.push(Sound {
+            path: "bird_sounds/chip.mp3".to_string(),
+            default_: true,
+            type_: Some("call".to_string()),
+            attribution: None,
+            duration_ms: Some(500),
+        });
+        assert!(quick.validate_timing(&pack, now).is_ok());
+        quick.answers[0].response_time_ms = 400;
+        assert!(quick.validate_timing(&pack, now).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+//! Signing game sessions, so that the server can tell the ones it started apart from forgeries.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::GameSession;
+use crate::pack::PackIdentifier;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Start a session, signed with `key`.
+pub fn start(
+    user_id: String,
+    pack: PackIdentifier,
+    started_at: DateTime<Utc>,
+    key: &[u8],
+) -> GameSession {
+    let mut session = GameSession {
+        user_id,
+        pack,
+        started_at,
+        nonce: rand::random(),
+        signature: String::new(),
+    };
+    session.signature = hex::encode(mac(&session, key).finalize().into_bytes());
+    session
+}
+
+/// Whether the session was signed with `key`, and hasn't been changed since. Only the signature
+/// as it was issued is accepted, i.e. in lowercase, so that there's only one way to spell it.
+pub fn verify(session: &GameSession, key: &[u8]) -> bool {
+    !session.signature.bytes().any(|b| b.is_ascii_uppercase())
+        && hex::decode(&session.signature)
+            .is_ok_and(|signature| mac(session, key).verify_slice(&signature).is_ok())
+}
+
+fn mac(session: &GameSession, key: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC takes keys of any size");
+    let message = format!(
+        "{}|{}|{}|{}",
+        session.user_id,
+        session.pack,
+        session.started_at.to_rfc3339(),
+        session.nonce
+    );
+    mac.update(message.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_only_unchanged_sessions() {
+        let session = start(
+            "user".to_string(),
+            PackIdentifier::Id(1),
+            Utc::now(),
+            b"key",
+        );
+        assert!(verify(&session, b"key"));
+        assert!(!verify(&session, b"other key"));
+
+        let mut other_pack = session.clone();
+        other_pack.pack = PackIdentifier::Id(2);
+        assert!(!verify(&other_pack, b"key"));
+        let mut earlier = session.clone();
+        earlier.started_at -= chrono::Duration::hours(1);
+        assert!(!verify(&earlier, b"key"));
+        // e.g. to record the game again under another primary key
+        let mut uppercase = session.clone();
+        uppercase.signature = uppercase.signature.to_uppercase();
+        assert!(!verify(&uppercase, b"key"));
+    }
+}
@@ -1,22 +1,145 @@
 //! This module contains progress functionality that is _permanent_ rather than per game session.
+//!
+//! Progress is kept as an append-only log of what happened while playing, i.e. every answer and
+//! every pack completed, and [`Stats`] are a projection of it. So stats can be recomputed when the
+//! rules for them change, and logs from different devices can be merged without losing anything.
 
-use std::collections::HashMap;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+};
 
-use chrono::NaiveDate;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    achievements::{Achievement, FamilyBirds, ACHIEVEMENTS},
+    clock::Clock,
     pack::{Pack, PackIdentifier},
-    utils,
 };
 
-/// The number of times a bird must be correctly identified consecutively to be considered learned.
+/// The number of times a bird must be correctly identified consecutively to be considered learned,
+/// where fluent answers count double, see [`learn_credit`].
 pub const LEARN_THRESHOLD: u32 = 3;
+/// Correct answers quicker than this (after the recording starts) show the bird is recognized
+/// fluently, rather than worked out.
+pub const FLUENT_RESPONSE_TIME_MS: u32 = 2000;
 pub const BIRDS_PER_LEVEL: usize = 15;
+/// A streak freeze is earned for every this many days in a row the daily pack is completed.
+pub const STREAK_FREEZE_DAYS: u32 = 7;
+/// The most streak freezes that can be held at once.
+pub const MAX_STREAK_FREEZES: u32 = 2;
+
+/// How a bird was quizzed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    /// Picking the bird that's heard out of a few
+    #[default]
+    MultipleChoice,
+}
+
+/// An answer given in a game.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Answer {
+    pub at: DateTime<Utc>,
+    /// When the game it was given in started, which tells games apart
+    pub game_started_at: DateTime<Utc>,
+    /// The bird that was to be identified
+    pub bird_id: u64,
+    pub pack: PackIdentifier,
+    #[serde(default)]
+    pub mode: Mode,
+    /// How long it took to answer, in milliseconds
+    pub response_time_ms: u32,
+    /// The bird that was chosen, which is a distractor if it isn't [`Answer::bird_id`]
+    pub chosen: u64,
+}
+
+impl Answer {
+    pub fn is_correct(&self) -> bool {
+        self.chosen == self.bird_id
+    }
+
+    pub fn learn_credit(&self) -> u32 {
+        learn_credit(self.is_correct(), self.response_time_ms)
+    }
+}
+
+/// How much an answer counts toward learning a bird: nothing if it's wrong, and double if it's
+/// [fluent](FLUENT_RESPONSE_TIME_MS).
+pub fn learn_credit(correct: bool, response_time_ms: u32) -> u32 {
+    match (correct, response_time_ms < FLUENT_RESPONSE_TIME_MS) {
+        (false, _) => 0,
+        (true, false) => 1,
+        (true, true) => 2,
+    }
+}
+
+/// A pack played to completion.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PackCompleted {
+    pub at: DateTime<Utc>,
+    pub pack: PackIdentifier,
+    /// See [`Pack::birdpack_id`]
+    pub birdpack_id: Option<u64>,
+    /// Whether it was a past daily pack, played from the archive
+    #[serde(default)]
+    pub from_archive: bool,
+}
+
+/// Something that happened while playing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Answer(Answer),
+    PackCompleted(PackCompleted),
+}
+
+impl Event {
+    pub fn at(&self) -> DateTime<Utc> {
+        match self {
+            Event::Answer(answer) => answer.at,
+            Event::PackCompleted(completed) => completed.at,
+        }
+    }
+}
 
 /// Learning progress for a user.
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StoredStats", into = "StoredStats")]
 pub struct Stats {
+    /// Progress from before there was a log, which is never updated
+    baseline: Projection,
+
+    /// Everything that has happened since, oldest first
+    log: Vec<Event>,
+
+    /// The baseline with the log applied
+    projection: Projection,
+
+    /// Per game and bird, the number of times it's been correctly identified consecutively
+    consecutive: HashMap<(DateTime<Utc>, u64), u32>,
+
+    /// When each achievement was unlocked, by id. These aren't derived from the log, since they
+    /// are kept even if what unlocked them no longer holds.
+    achievements: BTreeMap<String, DateTime<Utc>>,
+
+    /// Streak freezes earned and used. Like achievements, these aren't derived from the log, so
+    /// that they stay earned and used whatever happens to the streaks later.
+    streak_freezes: StreakFreezes,
+
+    /// The time zone days are counted in for daily packs, see [`Stats::time_zone`]
+    time_zone: Option<Tz>,
+
+    /// What time it is, which isn't stored
+    clock: Clock,
+}
+
+/// Aggregate stats, which are derived from the log.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Projection {
     /// Per bird stats
     bird_stats: HashMap<u64, BirdStats>,
 
@@ -26,11 +149,80 @@ pub struct Stats {
     /// Daily pack stats
     daily_packs_completed: Vec<NaiveDate>,
 
+    /// Daily packs completed from the archive, i.e. days after their own, which don't count
+    /// toward streaks
+    late_daily_packs_completed: BTreeSet<NaiveDate>,
+
     /// Current consecutive correct ID streak.
     current_streak: u32,
 
     /// Record number of consecutive correct bird IDs.
     record_streak: u32,
+
+    /// Per bird and the bird it was mistaken for, the number of times it happened.
+    confusions: HashMap<(u64, u64), u32>,
+}
+
+/// How stats are stored. Stats from before the log existed are stored as just the aggregates,
+/// which become the baseline. The baseline's fields are spelled out rather than flattened, since
+/// flattening would deserialize the bird ids keying `bird_stats` as strings.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct StoredStats {
+    bird_stats: HashMap<u64, BirdStats>,
+    pack_stats: HashMap<u64, BirdPackStats>,
+    daily_packs_completed: Vec<NaiveDate>,
+    current_streak: u32,
+    record_streak: u32,
+    log: Vec<Event>,
+    achievements: BTreeMap<String, DateTime<Utc>>,
+    streak_freezes: StreakFreezes,
+    /// The pinned time zone's IANA name, e.g. America/New_York
+    time_zone: Option<String>,
+}
+
+impl From<StoredStats> for Stats {
+    fn from(stored: StoredStats) -> Self {
+        let baseline = Projection {
+            bird_stats: stored.bird_stats,
+            pack_stats: stored.pack_stats,
+            daily_packs_completed: stored.daily_packs_completed,
+            current_streak: stored.current_streak,
+            record_streak: stored.record_streak,
+            // there was no record of which birds were chosen before the log, nor of packs
+            // completed from the archive
+            confusions: HashMap::new(),
+            late_daily_packs_completed: BTreeSet::new(),
+        };
+        let mut stats = Self {
+            baseline,
+            log: stored.log,
+            achievements: stored.achievements,
+            streak_freezes: stored.streak_freezes,
+            // a zone this build doesn't know of counts days in the device's time zone
+            time_zone: stored.time_zone.and_then(|name| name.parse().ok()),
+            ..Default::default()
+        };
+        stats.project();
+        stats
+    }
+}
+
+impl From<Stats> for StoredStats {
+    fn from(stats: Stats) -> Self {
+        let baseline = stats.baseline;
+        Self {
+            bird_stats: baseline.bird_stats,
+            pack_stats: baseline.pack_stats,
+            daily_packs_completed: baseline.daily_packs_completed,
+            current_streak: baseline.current_streak,
+            record_streak: baseline.record_streak,
+            log: stats.log,
+            achievements: stats.achievements,
+            streak_freezes: stats.streak_freezes,
+            time_zone: stats.time_zone.map(|tz| tz.name().to_string()),
+        }
+    }
 }
 
 /// Stats per bird for a user.
@@ -39,6 +231,89 @@ pub struct BirdStats {
     pub identified: u32,
     pub mistaken: u32,
     pub learned: bool,
+    /// The number of correct answers that were timed, i.e. not from before answers were logged
+    #[serde(default)]
+    pub timed: u32,
+    /// The total time taken by timed answers, in milliseconds
+    #[serde(default)]
+    pub total_response_time_ms: u64,
+}
+
+impl BirdStats {
+    /// How long it takes to recognize the bird on average, in milliseconds
+    pub fn average_response_time_ms(&self) -> Option<u32> {
+        (self.timed > 0).then(|| (self.total_response_time_ms / self.timed as u64) as u32)
+    }
+}
+
+/// What was played on a day, see [`Stats::history`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct DayProgress {
+    pub date: NaiveDate,
+    pub answers: u32,
+    pub correct: u32,
+    /// The total number of birds learned by the end of the day
+    pub birds_learned: u32,
+}
+
+impl DayProgress {
+    /// The fraction of answers that were correct
+    pub fn accuracy(&self) -> f64 {
+        if self.answers == 0 {
+            0.0
+        } else {
+            self.correct as f64 / self.answers as f64
+        }
+    }
+}
+
+/// Two birds that have been mistaken for one another.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConfusedPair {
+    /// The birds' ids, lowest first
+    pub birds: (u64, u64),
+    /// The number of times either was mistaken for the other
+    pub times: u32,
+}
+
+/// Daily pack streaks, see [`Stats::daily_pack_streak`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DailyPackStreak {
+    /// Days in a row the daily pack was completed, up to today or yesterday (or a day before the
+    /// ones streak freezes were used for), otherwise 0
+    pub active: u32,
+    /// The most recent streak, whether or not it's still active
+    pub latest: u32,
+    /// Streak freezes held, each of which keeps a streak going over a missed day
+    pub freezes: u32,
+    /// The missed days that streak freezes were (or are about to be) used for, oldest first
+    pub frozen: Vec<NaiveDate>,
+}
+
+/// Streak freezes, each of which keeps a daily pack streak going over a missed day.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct StreakFreezes {
+    /// The days whose daily pack earned a streak freeze
+    earned: BTreeSet<NaiveDate>,
+    /// The missed days streak freezes were used for
+    used: BTreeSet<NaiveDate>,
+}
+
+impl StreakFreezes {
+    /// The number earned but not used yet
+    fn held(&self) -> u32 {
+        self.earned.len().saturating_sub(self.used.len()) as u32
+    }
+
+    /// The days between `last` and `day` that weren't played, nor covered by a streak freeze.
+    fn missed(&self, last: NaiveDate, day: NaiveDate) -> Vec<NaiveDate> {
+        last.iter_days()
+            .skip(1)
+            .take_while(|d| *d < day)
+            .filter(|d| !self.used.contains(d))
+            .collect()
+    }
 }
 
 /// Stats per bird pack for a user.
@@ -48,111 +323,407 @@ pub struct BirdPackStats {
 }
 
 impl Stats {
+    /// Use `clock` for the current time, e.g. a fixed one in tests.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> Clock {
+        self.clock
+    }
+
+    /// The time zone days are counted in for daily packs, if it's been pinned. Until it is, days
+    /// are counted in the device's time zone, so travelling would skip or repeat a day and could
+    /// break the streak.
+    pub fn time_zone(&self) -> Option<Tz> {
+        self.time_zone
+    }
+
+    /// Count days in `time_zone`, including those already played.
+    pub fn pin_time_zone(&mut self, time_zone: Tz) {
+        self.time_zone = Some(time_zone);
+        self.project();
+    }
+
+    /// What day it is in the user's time zone.
+    pub fn today(&self) -> NaiveDate {
+        date_in(self.clock.now(), self.time_zone)
+    }
+
     pub fn xp(&self) -> u32 {
-        self.bird_stats
-            .values()
-            .map(|bs| if bs.learned { 10 } else { 0 } + bs.identified)
-            .sum()
+        self.projection.xp()
     }
 
     pub fn birds_learned(&self) -> Vec<u64> {
-        self.bird_stats
+        self.projection
+            .bird_stats
             .iter()
             .filter_map(|(id, bs)| if bs.learned { Some(*id) } else { None })
             .collect()
     }
 
     pub fn total_birds_learned(&self) -> u32 {
-        self.bird_stats.values().filter(|bs| bs.learned).count() as u32
+        self.projection.total_birds_learned()
     }
 
     pub fn level(&self) -> u32 {
-        1 + (self.bird_stats.values().filter(|bs| bs.learned).count() / BIRDS_PER_LEVEL) as u32
+        1 + (self.total_birds_learned() as usize / BIRDS_PER_LEVEL) as u32
+    }
+
+    /// Everything that has happened, oldest first. This doesn't include progress from before
+    /// there was a log.
+    pub fn log(&self) -> &[Event] {
+        &self.log
     }
 
-    pub fn add_correct_id(&mut self, learned: bool, bird_id: u64) {
-        self.current_streak += 1;
-        if self.current_streak > self.record_streak {
-            self.record_streak = self.current_streak;
+    /// Progress on each day anything was logged, oldest first.
+    pub fn history(&self) -> Vec<DayProgress> {
+        let mut projection = self.baseline.clone();
+        let mut consecutive = HashMap::new();
+        let mut days: Vec<DayProgress> = Vec::new();
+        for event in &self.log {
+            projection.apply(event, &mut consecutive, self.time_zone);
+            let date = date_in(event.at(), self.time_zone);
+            let day = match days.last_mut() {
+                Some(day) if day.date == date => day,
+                _ => {
+                    days.push(DayProgress {
+                        date,
+                        ..Default::default()
+                    });
+                    days.last_mut().unwrap()
+                }
+            };
+            if let Event::Answer(answer) = event {
+                day.answers += 1;
+                day.correct += answer.is_correct() as u32;
+            }
+            day.birds_learned = projection.total_birds_learned();
         }
-        let bird_stat = self.bird_stats.entry(bird_id).or_default();
-        bird_stat.identified += 1;
-        bird_stat.learned |= learned;
+        days
     }
 
-    pub fn add_incorrect_id(&mut self, bird_id: u64) {
-        self.current_streak = 0;
-        let bird_stat = self.bird_stats.entry(bird_id).or_default();
-        bird_stat.mistaken += 1;
+    /// The days whose daily pack was completed, oldest first.
+    pub fn daily_packs_completed(&self) -> &[NaiveDate] {
+        &self.projection.daily_packs_completed
     }
 
-    pub fn add_pack_completed(&mut self, pack: &Pack) {
-        if let Some(pack_id) = pack.birdpack_id {
-            let pack_stat = self.pack_stats.entry(pack_id).or_default();
-            pack_stat.times_completed += 1;
-        }
+    /// The days whose daily pack was only completed later, from the archive, oldest first.
+    pub fn late_daily_packs_completed(&self) -> &BTreeSet<NaiveDate> {
+        &self.projection.late_daily_packs_completed
+    }
 
-        // If this is a daily pack
-        if let PackIdentifier::Date(day) = pack.id {
-            // that is actually today's pack (or yesterday's, allowing for fetched/finished
-            // before/after midnight)
-            let today = utils::today();
-            if day == today || day == today.pred_opt().unwrap() {
-                // that hasn't been completed yet
-                if self
-                    .daily_packs_completed
-                    .last()
-                    .filter(|d| **d >= day)
-                    .is_none()
-                {
-                    // then record it
-                    self.daily_packs_completed.push(day);
+    /// The answers given in the game that started at `game_started_at`
+    pub fn game_answers(&self, game_started_at: DateTime<Utc>) -> Vec<Answer> {
+        self.log
+            .iter()
+            .filter_map(|event| match event {
+                Event::Answer(answer) if answer.game_started_at == game_started_at => {
+                    Some(answer.clone())
                 }
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn record_answer(&mut self, answer: Answer) {
+        self.record(Event::Answer(answer));
+    }
+
+    pub fn record_pack_completed(&mut self, pack: &Pack, at: DateTime<Utc>, from_archive: bool) {
+        self.record(Event::PackCompleted(PackCompleted {
+            at,
+            pack: pack.id.clone(),
+            birdpack_id: pack.birdpack_id,
+            from_archive,
+        }));
+    }
+
+    /// Append an event to the log. Events are usually recorded as they happen, but if this one
+    /// happened before the latest, it's put in its place and the stats are recomputed.
+    pub fn record(&mut self, event: Event) {
+        let in_order = match self.log.last() {
+            Some(latest) => latest.at() <= event.at(),
+            None => true,
+        };
+        let completed_pack = matches!(event, Event::PackCompleted(_));
+        if in_order {
+            self.projection
+                .apply(&event, &mut self.consecutive, self.time_zone);
+            self.log.push(event);
+        } else {
+            let index = self.log.partition_point(|e| e.at() <= event.at());
+            self.log.insert(index, event);
+            self.project();
+        }
+        if completed_pack {
+            self.update_streak_freezes();
+        }
+    }
+
+    /// Use streak freezes for the days missed before the latest daily pack, if there are enough
+    /// for them all, and earn one for every [`STREAK_FREEZE_DAYS`] days of its streak, up to
+    /// [`MAX_STREAK_FREEZES`] held at once.
+    fn update_streak_freezes(&mut self) {
+        let completed = &self.projection.daily_packs_completed;
+        let Some(&day) = completed.last() else {
+            return;
+        };
+        if let Some(&previous) = completed.iter().rev().nth(1) {
+            let missed = self.streak_freezes.missed(previous, day);
+            if !missed.is_empty() && missed.len() as u32 <= self.streak_freezes.held() {
+                self.streak_freezes.used.extend(missed);
             }
         }
+        if self
+            .daily_pack_streak()
+            .latest
+            .is_multiple_of(STREAK_FREEZE_DAYS)
+            && self.streak_freezes.held() < MAX_STREAK_FREEZES
+        {
+            self.streak_freezes.earned.insert(day);
+        }
+    }
+
+    /// The achievements unlocked so far, with when they were unlocked, in the order they're shown.
+    pub fn achievements(&self) -> Vec<(&'static Achievement, DateTime<Utc>)> {
+        ACHIEVEMENTS
+            .iter()
+            .filter_map(|a| Some((a, *self.achievements.get(a.id)?)))
+            .collect()
+    }
+
+    /// Unlock the achievements that have been met, having just completed `pack`, returning those
+    /// that are new. `families` are the birds of the families in the pack, see
+    /// [`Achievement::families_in`].
+    pub fn unlock_achievements(
+        &mut self,
+        pack: &Pack,
+        families: &FamilyBirds,
+        at: DateTime<Utc>,
+    ) -> Vec<&'static Achievement> {
+        let unlocked: Vec<_> = ACHIEVEMENTS
+            .iter()
+            .filter(|a| !self.achievements.contains_key(a.id) && a.is_met(self, pack, families))
+            .collect();
+        for achievement in &unlocked {
+            self.achievements.insert(achievement.id.to_string(), at);
+        }
+        unlocked
+    }
+
+    /// Merge in stats from elsewhere, e.g. another device, keeping every event and achievement
+    /// from both. Only one baseline can be kept, so it's the one with the most progress.
+    pub fn merge(&mut self, other: &Stats) {
+        if other.baseline.xp() > self.baseline.xp() {
+            self.baseline = other.baseline.clone();
+        }
+        self.time_zone = self.time_zone.or(other.time_zone);
+        for (id, at) in &other.achievements {
+            let unlocked_at = self.achievements.entry(id.clone()).or_insert(*at);
+            *unlocked_at = (*unlocked_at).min(*at);
+        }
+        let freezes = &other.streak_freezes;
+        self.streak_freezes.earned.extend(&freezes.earned);
+        self.streak_freezes.used.extend(&freezes.used);
+        self.log.extend(other.log.iter().cloned());
+        self.log.sort_by_key(Event::at);
+        // events at the same time needn't be next to each other after sorting
+        let mut seen = HashSet::new();
+        self.log.retain(|event| seen.insert(event.clone()));
+        self.project();
+    }
+
+    /// Compute the stats from scratch.
+    fn project(&mut self) {
+        self.projection = self.baseline.clone();
+        self.consecutive.clear();
+        for event in &self.log {
+            self.projection
+                .apply(event, &mut self.consecutive, self.time_zone);
+        }
     }
 
     /// Returns the active daily pack streak, if any.
     ///
-    /// For a streak to be active, it must have continued until at latest yesterday.
+    /// For a streak to be active, it must have continued until at latest yesterday, or until
+    /// the days missed since were covered by streak freezes.
     pub fn active_daily_pack_streak(&self) -> u32 {
-        let today = utils::today();
-        self.daily_pack_streak(|d| *d == today || *d == today.pred_opt().unwrap())
-            .unwrap_or(0)
+        self.daily_pack_streak().active
     }
 
     /// Returns the latest daily pack streak, if any.
     ///
     /// This is the most recent pack strek, regardless of whether it is still active.
     pub fn latest_daily_pack_streak(&self) -> u32 {
-        self.daily_pack_streak(|_| true).unwrap_or(0)
-    }
-
-    /// Returns a daily pack streak where the most recent pack completed satisfies the given
-    /// predicate. If there are no packs completed or the predicate fails, returns `None`.
-    fn daily_pack_streak<F>(&self, start_date_predicate: F) -> Option<u32>
-    where
-        F: Fn(&NaiveDate) -> bool,
-    {
-        let mut day = self
-            .daily_packs_completed
-            .last()
-            .copied()
-            .filter(start_date_predicate)?;
-        let mut count = 0;
-        for day_completed in self.daily_packs_completed.iter().rev() {
-            if day == *day_completed {
-                count += 1;
-                day = day.pred_opt().unwrap();
-            } else {
-                break;
+        self.daily_pack_streak().latest
+    }
+
+    /// The daily pack streaks as of today, which carry on over days streak freezes were used for.
+    /// Days missed since the latest daily pack will use streak freezes once the next one is
+    /// completed, if there are enough for them all, so until then the streak is still active.
+    pub fn daily_pack_streak(&self) -> DailyPackStreak {
+        let freezes = &self.streak_freezes;
+        let mut streak = DailyPackStreak {
+            freezes: freezes.held(),
+            frozen: freezes.used.iter().copied().collect(),
+            ..Default::default()
+        };
+        let mut last: Option<NaiveDate> = None;
+        for &day in &self.projection.daily_packs_completed {
+            if !last.is_some_and(|last| freezes.missed(last, day).is_empty()) {
+                streak.latest = 0;
+            }
+            streak.latest += 1;
+            last = Some(day);
+        }
+        // today's pack can still be completed, so only the days before it are missed
+        if let Some(last) = last {
+            let missed = freezes.missed(last, self.today());
+            if missed.len() as u32 <= streak.freezes {
+                streak.active = streak.latest;
+                streak.freezes -= missed.len() as u32;
+                streak.frozen.extend(missed);
             }
         }
-        Some(count)
+        streak
+    }
+
+    pub fn record_streak(&self) -> u32 {
+        self.projection.record_streak
     }
 
     pub fn bird_stats(&self) -> &HashMap<u64, BirdStats> {
-        &self.bird_stats
+        &self.projection.bird_stats
+    }
+
+    /// The number of times `bird_id` was mistaken for `chosen`.
+    pub fn times_confused(&self, bird_id: u64, chosen: u64) -> u32 {
+        self.projection
+            .confusions
+            .get(&(bird_id, chosen))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Every pair of birds that have been mistaken for one another, either way round, most
+    /// confused first.
+    pub fn most_confused_pairs(&self) -> Vec<ConfusedPair> {
+        let mut pairs: HashMap<(u64, u64), u32> = HashMap::new();
+        for (&(bird_id, chosen), &times) in &self.projection.confusions {
+            *pairs
+                .entry((bird_id.min(chosen), bird_id.max(chosen)))
+                .or_default() += times;
+        }
+        let mut pairs: Vec<_> = pairs
+            .into_iter()
+            .map(|(birds, times)| ConfusedPair { birds, times })
+            .collect();
+        pairs.sort_by_key(|pair| (Reverse(pair.times), pair.birds));
+        pairs
+    }
+
+    /// Whether the bird can be played in a review, i.e. it's been learned, or it's been mistaken
+    /// for another bird or vice versa.
+    pub fn can_review(&self, bird_id: u64) -> bool {
+        self.projection
+            .bird_stats
+            .get(&bird_id)
+            .is_some_and(|bs| bs.learned)
+            || self
+                .projection
+                .confusions
+                .keys()
+                .any(|&(bird, chosen)| bird == bird_id || chosen == bird_id)
+    }
+}
+
+impl Projection {
+    fn total_birds_learned(&self) -> u32 {
+        self.bird_stats.values().filter(|bs| bs.learned).count() as u32
+    }
+
+    fn xp(&self) -> u32 {
+        self.bird_stats
+            .values()
+            .map(|bs| if bs.learned { 10 } else { 0 } + bs.identified)
+            .sum()
+    }
+
+    fn apply(
+        &mut self,
+        event: &Event,
+        consecutive: &mut HashMap<(DateTime<Utc>, u64), u32>,
+        time_zone: Option<Tz>,
+    ) {
+        match event {
+            Event::Answer(answer) => self.apply_answer(answer, consecutive),
+            Event::PackCompleted(completed) => self.apply_pack_completed(completed, time_zone),
+        }
+    }
+
+    fn apply_answer(
+        &mut self,
+        answer: &Answer,
+        consecutive: &mut HashMap<(DateTime<Utc>, u64), u32>,
+    ) {
+        let count = consecutive
+            .entry((answer.game_started_at, answer.bird_id))
+            .or_default();
+        let bird_stat = self.bird_stats.entry(answer.bird_id).or_default();
+        if answer.is_correct() {
+            *count += answer.learn_credit();
+            self.current_streak += 1;
+            if self.current_streak > self.record_streak {
+                self.record_streak = self.current_streak;
+            }
+            bird_stat.identified += 1;
+            bird_stat.learned |= *count >= LEARN_THRESHOLD;
+            bird_stat.timed += 1;
+            bird_stat.total_response_time_ms += answer.response_time_ms as u64;
+        } else {
+            *count = 0;
+            self.current_streak = 0;
+            bird_stat.mistaken += 1;
+            *self
+                .confusions
+                .entry((answer.bird_id, answer.chosen))
+                .or_default() += 1;
+        }
+    }
+
+    fn apply_pack_completed(&mut self, completed: &PackCompleted, time_zone: Option<Tz>) {
+        if let Some(pack_id) = completed.birdpack_id {
+            let pack_stat = self.pack_stats.entry(pack_id).or_default();
+            pack_stat.times_completed += 1;
+        }
+
+        // If this is a daily pack
+        if let PackIdentifier::Date(day) = completed.pack {
+            // that was actually that day's pack (or the day before's or after's, allowing for
+            // fetched/finished before/after midnight, or fetched in another time zone), rather
+            // than one picked from the archive
+            let completed_on = date_in(completed.at, time_zone);
+            let on_time = (day - completed_on).num_days().abs() <= 1 && !completed.from_archive;
+            // that hasn't been completed yet (nor a later day's)
+            let completed_before = self.daily_packs_completed.last().is_some_and(|d| *d >= day);
+            if on_time && !completed_before {
+                // then record it
+                self.daily_packs_completed.push(day);
+            } else if !self.daily_packs_completed.contains(&day) {
+                // otherwise it was played from the archive, which is only worth XP
+                self.late_daily_packs_completed.insert(day);
+            }
+        }
+    }
+}
+
+/// The date at `at` in `time_zone`, or in the device's time zone if there's none.
+fn date_in(at: DateTime<Utc>, time_zone: Option<Tz>) -> NaiveDate {
+    match time_zone {
+        Some(time_zone) => at.with_timezone(&time_zone).date_naive(),
+        None => at.with_timezone(&Local).date_naive(),
     }
 }
 
@@ -160,44 +731,369 @@ impl Stats {
 mod tests {
     use super::*;
 
+    fn date(date: &str) -> NaiveDate {
+        date.parse().unwrap()
+    }
+
+    /// Stats at `time` (UTC) on `date`, counting days in UTC unless they're pinned to another time
+    /// zone.
+    fn stats_at(date: &str, time: &str) -> Stats {
+        let now = format!("{date}T{time}Z").parse().unwrap();
+        let mut stats = Stats::default().with_clock(Clock::Fixed(now));
+        stats.pin_time_zone(Tz::UTC);
+        stats
+    }
+
+    fn completed(day: NaiveDate) -> Event {
+        completed_on(day, day)
+    }
+
+    fn completed_on(day: NaiveDate, on: NaiveDate) -> Event {
+        Event::PackCompleted(PackCompleted {
+            at: on.and_hms_opt(12, 0, 0).unwrap().and_utc(),
+            pack: PackIdentifier::Date(day),
+            birdpack_id: None,
+            from_archive: false,
+        })
+    }
+
+    fn completed_from_archive(day: NaiveDate, on: NaiveDate) -> Event {
+        Event::PackCompleted(PackCompleted {
+            at: on.and_hms_opt(12, 0, 0).unwrap().and_utc(),
+            pack: PackIdentifier::Date(day),
+            birdpack_id: None,
+            from_archive: true,
+        })
+    }
+
+    fn answer(game: i64, secs: i64, bird_id: u64, chosen: u64) -> Event {
+        timed_answer(game, secs, bird_id, chosen, FLUENT_RESPONSE_TIME_MS)
+    }
+
+    fn timed_answer(
+        game: i64,
+        secs: i64,
+        bird_id: u64,
+        chosen: u64,
+        response_time_ms: u32,
+    ) -> Event {
+        let start = DateTime::from_timestamp(0, 0).unwrap();
+        Event::Answer(Answer {
+            at: start + chrono::Duration::seconds(secs),
+            game_started_at: start + chrono::Duration::seconds(game),
+            bird_id,
+            pack: PackIdentifier::Id(1),
+            mode: Mode::MultipleChoice,
+            response_time_ms,
+            chosen,
+        })
+    }
+
     #[test]
     fn active_streak_can_start_yesterday() {
-        let yesterday = utils::today().pred_opt().unwrap();
-        let stats = Stats {
-            daily_packs_completed: vec![yesterday.pred_opt().unwrap(), yesterday],
-            ..Default::default()
-        };
+        let mut stats = stats_at("2024-10-03", "12:00:00");
+        stats.record(completed(date("2024-10-01")));
+        stats.record(completed(date("2024-10-02")));
 
         assert_eq!(stats.active_daily_pack_streak(), 2);
     }
 
     #[test]
     fn active_pack_streak_can_start_today() {
-        let today = utils::today();
-        let stats = Stats {
-            daily_packs_completed: vec![today.pred_opt().unwrap(), today],
-            ..Default::default()
-        };
+        let mut stats = stats_at("2024-10-03", "12:00:00");
+        stats.record(completed(date("2024-10-02")));
+        stats.record(completed(date("2024-10-03")));
+        assert_eq!(stats.active_daily_pack_streak(), 2);
         assert_eq!(stats.latest_daily_pack_streak(), 2);
     }
 
     #[test]
     fn daily_pack_streak_lost_works() {
-        let today = utils::today();
-        let yesterday = today.pred_opt().unwrap();
-        let yyesterday = yesterday.pred_opt().unwrap();
-        let yyyesterday = yyesterday.pred_opt().unwrap();
-        let mut stats = Stats::default();
-        stats.daily_packs_completed.push(yyyesterday);
-        stats.daily_packs_completed.push(yyesterday);
+        let mut stats = stats_at("2024-10-04", "12:00:00");
+        stats.record(completed(date("2024-10-01")));
+        stats.record(completed(date("2024-10-02")));
 
         assert_eq!(stats.active_daily_pack_streak(), 0);
         assert_eq!(stats.latest_daily_pack_streak(), 2);
 
         // skip yesterday
-        stats.daily_packs_completed.push(today);
+        stats.record(completed(date("2024-10-04")));
 
         assert_eq!(stats.active_daily_pack_streak(), 1);
         assert_eq!(stats.latest_daily_pack_streak(), 1);
     }
+
+    #[test]
+    fn counts_days_in_the_pinned_time_zone() {
+        // 9pm on the 3rd in New York
+        let mut stats = stats_at("2024-10-04", "01:00:00");
+        stats.record(completed(date("2024-10-01")));
+        stats.record(completed(date("2024-10-02")));
+        assert_eq!(stats.today(), date("2024-10-04"));
+        assert_eq!(stats.active_daily_pack_streak(), 0);
+
+        stats.pin_time_zone(Tz::America__New_York);
+        assert_eq!(stats.today(), date("2024-10-03"));
+        assert_eq!(stats.active_daily_pack_streak(), 2);
+        // including when its clocks change, unlike an offset from UTC
+        let winter = stats
+            .clone()
+            .with_clock(Clock::Fixed("2024-12-04T04:30:00Z".parse().unwrap()));
+        assert_eq!(winter.today(), date("2024-12-03"));
+
+        // the pin is kept, even by devices in another time zone
+        let reloaded: Stats =
+            serde_json::from_str(&serde_json::to_string(&stats).unwrap()).unwrap();
+        let mut merged = Stats::default();
+        merged.merge(&reloaded);
+        assert_eq!(merged.time_zone(), stats.time_zone());
+    }
+
+    #[test]
+    fn streak_freezes_cover_missed_days() {
+        let mut stats = stats_at("2024-10-09", "12:00:00");
+        for day in date("2024-10-01")
+            .iter_days()
+            .take(STREAK_FREEZE_DAYS as usize)
+        {
+            stats.record(completed(day));
+        }
+        // the freeze earned covers yesterday
+        let streak = stats.daily_pack_streak();
+        assert_eq!((streak.active, streak.freezes), (7, 0));
+        assert_eq!(streak.frozen, vec![date("2024-10-08")]);
+
+        stats.record(completed(date("2024-10-09")));
+        assert_eq!(stats.active_daily_pack_streak(), 8);
+
+        // and there's none left for another missed day
+        stats = stats.with_clock(Clock::Fixed("2024-10-11T12:00:00Z".parse().unwrap()));
+        let streak = stats.daily_pack_streak();
+        assert_eq!((streak.active, streak.latest), (0, 8));
+        assert_eq!(streak.frozen.len(), 1);
+    }
+
+    #[test]
+    fn keeps_streak_freezes_earned_and_used() {
+        let mut stats = stats_at("2024-10-21", "12:00:00");
+        for day in date("2024-10-01")
+            .iter_days()
+            .take(3 * STREAK_FREEZE_DAYS as usize)
+        {
+            stats.record(completed(day));
+        }
+        // no more than can be held at once are earned
+        assert_eq!(stats.daily_pack_streak().freezes, MAX_STREAK_FREEZES);
+
+        // a missed day uses one up once the next daily pack is completed
+        let before = stats.clone();
+        stats = stats.with_clock(Clock::Fixed("2024-10-23T12:00:00Z".parse().unwrap()));
+        stats.record(completed(date("2024-10-23")));
+        let streak = stats.daily_pack_streak();
+        assert_eq!((streak.active, streak.freezes), (22, 1));
+        assert_eq!(streak.frozen, vec![date("2024-10-22")]);
+
+        // and it stays used, even when merged with stats from before it was
+        let reloaded: Stats =
+            serde_json::from_str(&serde_json::to_string(&stats).unwrap()).unwrap();
+        let mut merged = before.with_clock(stats.clock());
+        merged.merge(&reloaded);
+        assert_eq!(merged.daily_pack_streak(), streak);
+    }
+
+    #[test]
+    fn late_daily_packs_dont_count_toward_streaks() {
+        let today = date("2024-10-05");
+        let mut stats = stats_at("2024-10-05", "12:00:00");
+        stats.record(completed_on(date("2024-10-01"), today));
+        assert!(stats.daily_packs_completed().is_empty());
+        assert_eq!(stats.active_daily_pack_streak(), 0);
+
+        stats.record(completed(today));
+        // yesterday's pack, but only after today's
+        stats.record(completed_on(date("2024-10-04"), today));
+        // and today's again, which was already completed on time
+        stats.record(completed(today));
+        assert_eq!(stats.daily_packs_completed(), [today]);
+        assert_eq!(stats.active_daily_pack_streak(), 1);
+        assert_eq!(
+            stats.late_daily_packs_completed(),
+            &BTreeSet::from([date("2024-10-01"), date("2024-10-04")])
+        );
+    }
+
+    #[test]
+    fn daily_packs_from_the_archive_dont_count_toward_streaks() {
+        let today = date("2024-10-05");
+        let mut stats = stats_at("2024-10-05", "12:00:00");
+        stats.record(completed(date("2024-10-03")));
+        // yesterday's pack, before today's, but picked from the archive
+        stats.record(completed_from_archive(date("2024-10-04"), today));
+        stats.record(completed(today));
+        assert_eq!(stats.daily_packs_completed(), [date("2024-10-03"), today]);
+        assert_eq!(stats.active_daily_pack_streak(), 1);
+        assert_eq!(
+            stats.late_daily_packs_completed(),
+            &BTreeSet::from([date("2024-10-04")])
+        );
+    }
+
+    #[test]
+    fn learns_birds_per_game() {
+        let mut stats = Stats::default();
+        // two correct answers in one game, and one in another, isn't enough
+        stats.record(answer(0, 1, 1, 1));
+        stats.record(answer(0, 2, 1, 1));
+        stats.record(answer(10, 11, 1, 1));
+        assert_eq!(stats.total_birds_learned(), 0);
+        // a mistake starts over
+        stats.record(answer(10, 12, 1, 2));
+        stats.record(answer(10, 13, 1, 1));
+        stats.record(answer(10, 14, 1, 1));
+        assert_eq!(stats.total_birds_learned(), 0);
+        stats.record(answer(10, 15, 1, 1));
+        assert_eq!(stats.total_birds_learned(), 1);
+        assert_eq!(stats.bird_stats()[&1].mistaken, 1);
+        assert_eq!(stats.xp(), 10 + 6);
+    }
+
+    #[test]
+    fn learns_fluently_recognized_birds_sooner() {
+        let mut stats = Stats::default();
+        stats.record(timed_answer(0, 1, 1, 1, 1500));
+        assert_eq!(stats.total_birds_learned(), 0);
+        stats.record(timed_answer(0, 2, 1, 1, 2500));
+        assert_eq!(stats.total_birds_learned(), 1);
+        assert_eq!(
+            stats.bird_stats()[&1].average_response_time_ms(),
+            Some(2000)
+        );
+        // mistakes aren't timed
+        stats.record(timed_answer(0, 3, 2, 1, 500));
+        assert_eq!(stats.bird_stats()[&2].average_response_time_ms(), None);
+    }
+
+    #[test]
+    fn keeps_a_daily_history() {
+        let day = 24 * 60 * 60;
+        let mut stats = Stats::default();
+        stats.record(answer(0, 1, 1, 1));
+        stats.record(answer(0, 2, 1, 2));
+        stats.record(answer(day, day + 1, 1, 1));
+        stats.record(answer(day, day + 2, 1, 1));
+        stats.record(answer(day, day + 3, 1, 1));
+        let history = stats.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!((history[0].answers, history[0].correct), (2, 1));
+        assert_eq!(history[0].birds_learned, 0);
+        assert_eq!(history[1].accuracy(), 1.0);
+        assert_eq!(history[1].birds_learned, 1);
+        assert_eq!(history[1].date, history[0].date.succ_opt().unwrap());
+    }
+
+    #[test]
+    fn tracks_confused_pairs() {
+        let mut stats = Stats::default();
+        stats.record(answer(0, 1, 1, 2));
+        stats.record(answer(0, 2, 1, 1));
+        stats.record(answer(0, 3, 2, 1));
+        stats.record(answer(0, 4, 3, 4));
+        stats.record(answer(0, 5, 2, 2));
+        assert_eq!(stats.times_confused(1, 2), 1);
+        assert_eq!(stats.times_confused(2, 1), 1);
+        assert_eq!(stats.times_confused(1, 3), 0);
+        assert_eq!(
+            stats.most_confused_pairs(),
+            vec![
+                ConfusedPair {
+                    birds: (1, 2),
+                    times: 2
+                },
+                ConfusedPair {
+                    birds: (3, 4),
+                    times: 1
+                },
+            ]
+        );
+        assert!(stats.can_review(4));
+        assert!(!stats.can_review(5));
+    }
+
+    #[test]
+    fn unlocks_achievements_once() {
+        let pack = Pack::from(Vec::new());
+        let now = DateTime::from_timestamp(60, 0).unwrap();
+        let mut stats = Stats::default();
+        assert!(stats
+            .unlock_achievements(&pack, &FamilyBirds::new(), now)
+            .is_empty());
+
+        stats.record(answer(0, 1, 1, 1));
+        stats.record(answer(0, 2, 1, 1));
+        stats.record(answer(0, 3, 1, 1));
+        let unlocked = stats.unlock_achievements(&pack, &FamilyBirds::new(), now);
+        assert_eq!(unlocked, vec![Achievement::by_id("first-bird").unwrap()]);
+        assert!(stats
+            .unlock_achievements(&pack, &FamilyBirds::new(), now)
+            .is_empty());
+
+        let reloaded: Stats =
+            serde_json::from_str(&serde_json::to_string(&stats).unwrap()).unwrap();
+        assert_eq!(reloaded.achievements().len(), 1);
+        let mut merged = Stats::default();
+        merged.merge(&reloaded);
+        assert_eq!(merged.achievements(), reloaded.achievements());
+    }
+
+    #[test]
+    fn merges_logs() {
+        let mut phone = Stats::default();
+        phone.record(answer(0, 1, 1, 1));
+        phone.record(answer(0, 3, 1, 1));
+        let mut laptop = phone.clone();
+        phone.record(answer(0, 5, 1, 1));
+        laptop.record(answer(0, 4, 1, 2));
+
+        let mut merged = phone.clone();
+        merged.merge(&laptop);
+        assert_eq!(merged.log().len(), 4);
+        assert_eq!(merged.bird_stats()[&1].identified, 3);
+        assert_eq!(merged.bird_stats()[&1].mistaken, 1);
+        // the mistake came before the last answer, so the bird wasn't learned
+        assert_eq!(merged.total_birds_learned(), 0);
+
+        laptop.merge(&phone);
+        assert_eq!(laptop, merged);
+    }
+
+    #[test]
+    fn merges_logs_with_simultaneous_events() {
+        let mut phone = Stats::default();
+        phone.record(answer(0, 1, 1, 1));
+        phone.record(answer(0, 1, 2, 2));
+        let laptop = phone.clone();
+
+        let mut merged = phone.clone();
+        merged.merge(&laptop);
+        assert_eq!(merged, phone);
+    }
+
+    #[test]
+    fn loads_stats_from_before_the_log() {
+        let stored = r#"{
+            "bird_stats": {"1": {"identified": 3, "mistaken": 0, "learned": true}},
+            "pack_stats": {},
+            "daily_packs_completed": [],
+            "current_streak": 3,
+            "record_streak": 3
+        }"#;
+        let mut stats: Stats = serde_json::from_str(stored).unwrap();
+        assert_eq!(stats.xp(), 13);
+
+        stats.record(answer(0, 1, 2, 2));
+        assert_eq!(stats.xp(), 14);
+        let reloaded: Stats =
+            serde_json::from_str(&serde_json::to_string(&stats).unwrap()).unwrap();
+        assert_eq!(reloaded, stats);
+    }
 }
@@ -6,7 +6,7 @@ use serde::{Deserialize, Deserializer, Serialize};
 use serde_querystring::UrlEncodedQS;
 use thiserror::Error;
 
-use crate::conf::{APP_URL, SUPABASE_ANON_KEY, SUPABASE_API_URL};
+use crate::conf;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct User {
@@ -62,6 +62,18 @@ impl AuthState {
         // TODO: when we set "Refreshing" we lose persisted data. Is that ok?
         let inner =
             use_synced_storage::<LocalStorage, _>("sb-auth".to_string(), || AuthStatus::None);
+        // authorize database requests as the user
+        use_effect(move || {
+            let token = match &*inner.read() {
+                AuthStatus::SignedIn(user) => Some(super::AccessToken {
+                    token: user.tokens.access_token.clone(),
+                    expires_at: user.tokens.expires_at as i64,
+                    user_id: user.sb_user.id.clone(),
+                }),
+                _ => None,
+            };
+            super::set_access_token(token);
+        });
         Self(inner)
     }
 
@@ -81,6 +93,11 @@ impl AuthState {
         self.with_user(|u| u.sb_user.id.clone())
     }
 
+    /// The signed in user's access token, e.g. to prove who they are to the server
+    pub fn access_token(&self) -> Option<String> {
+        self.with_user(|u| u.tokens.access_token.clone())
+    }
+
     pub fn user(&self) -> Option<User> {
         self.with_user(Clone::clone)
     }
@@ -93,10 +110,14 @@ impl AuthState {
     }
 
     pub async fn sign_in_with_magic_link(&self, email: String) -> Result<(), AuthError> {
-        gloo_net::http::Request::post(&format!("{SUPABASE_API_URL}/auth/v1/otp"))
-            .query([("redirect_to", format!("{APP_URL}/login"))])
-            .header("apikey", SUPABASE_ANON_KEY)
-            .header("Authorization", &format!("Bearer {SUPABASE_ANON_KEY}"))
+        let config = conf::get();
+        gloo_net::http::Request::post(&format!("{}/auth/v1/otp", config.supabase_api_url))
+            .query([("redirect_to", format!("{}/login", config.app_url))])
+            .header("apikey", &config.supabase_anon_key)
+            .header(
+                "Authorization",
+                &format!("Bearer {}", config.supabase_anon_key),
+            )
             .json(&MagicLinkRequest::new(email))?
             .send()
             .await?;
@@ -121,9 +142,10 @@ impl AuthState {
     }
 
     async fn get_user(&self, access_token: &str) -> Result<SupabaseUser, AuthError> {
+        let config = conf::get();
         let rsp: SupabaseUser =
-            gloo_net::http::Request::get(&format!("{SUPABASE_API_URL}/auth/v1/user"))
-                .header("apikey", SUPABASE_ANON_KEY)
+            gloo_net::http::Request::get(&format!("{}/auth/v1/user", config.supabase_api_url))
+                .header("apikey", &config.supabase_anon_key)
                 .header("Authorization", &format!("Bearer {access_token}"))
                 .send()
                 .await?
@@ -0,0 +1,227 @@
+//! A client side cache of [`super::SupabaseRequest`] responses, keyed by request URL.
+//!
+//! Entries are kept in memory and, on web, in local storage so that they survive page loads.
+//! Fresh entries are served without touching the network; stale entries are revalidated with
+//! their ETag (if any), and served as a fallback if the network fails. Local storage only keeps
+//! the [`MAX_STORED`] most recently fetched entries, none older than [`MAX_AGE`].
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the cache's local storage keys.
+const STORAGE_PREFIX: &str = "supabase-cache:";
+
+/// Key of the index of entries in local storage, see [`Index`].
+const INDEX_KEY: &str = "index";
+
+/// Most entries kept in local storage, which browsers only give a few megabytes.
+const MAX_STORED: usize = 200;
+
+/// Entries in local storage fetched longer ago than this are dropped. It's longer than any TTL,
+/// so that stale entries can still be served offline for a while.
+const MAX_AGE: chrono::Duration = chrono::Duration::days(30);
+
+/// When each entry in local storage was fetched, by key, so that entries can be evicted without
+/// reading them all.
+type Index = BTreeMap<String, DateTime<Utc>>;
+
+thread_local! {
+    static MEMORY: RefCell<HashMap<String, CachedResponse>> = RefCell::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// The raw response body
+    pub body: String,
+    pub etag: Option<String>,
+    #[serde(default)]
+    pub content_range: Option<String>,
+    /// When the response was last fetched or revalidated
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl CachedResponse {
+    pub fn new(body: String, etag: Option<String>, content_range: Option<String>) -> Self {
+        Self {
+            body,
+            etag,
+            content_range,
+            fetched_at: Utc::now(),
+        }
+    }
+
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        chrono::Duration::from_std(ttl)
+            .map(|ttl| Utc::now() < self.fetched_at + ttl)
+            .unwrap_or(false)
+    }
+
+    /// Mark the response as just revalidated.
+    pub fn touch(&mut self) {
+        self.fetched_at = Utc::now();
+    }
+}
+
+pub fn get(key: &str) -> Option<CachedResponse> {
+    if let Some(entry) = MEMORY.with(|memory| memory.borrow().get(key).cloned()) {
+        return Some(entry);
+    }
+    let entry: CachedResponse = serde_json::from_str(&storage_get(key)?).ok()?;
+    MEMORY.with(|memory| memory.borrow_mut().insert(key.to_string(), entry.clone()));
+    Some(entry)
+}
+
+pub fn put(key: &str, entry: CachedResponse) {
+    if let Ok(json) = serde_json::to_string(&entry) {
+        let mut index = load_index();
+        index.insert(key.to_string(), entry.fetched_at);
+        for key in evicted(&index, Utc::now()) {
+            index.remove(&key);
+            storage_remove(&key);
+        }
+        storage_set(key, &json);
+        save_index(&index);
+    }
+    MEMORY.with(|memory| memory.borrow_mut().insert(key.to_string(), entry));
+}
+
+/// Remove every entry of requests made as the user, e.g. once they've signed out, so that whoever
+/// uses the device next can't see them.
+pub fn remove_user(user_id: &str) {
+    let suffix = format!(" as {user_id}");
+    MEMORY.with(|memory| memory.borrow_mut().retain(|key, _| !key.ends_with(&suffix)));
+    let mut index = load_index();
+    let removed: Vec<String> = index
+        .keys()
+        .filter(|key| key.ends_with(&suffix))
+        .cloned()
+        .collect();
+    for key in removed {
+        index.remove(&key);
+        storage_remove(&key);
+    }
+    save_index(&index);
+}
+
+/// Keys of the entries to evict from local storage: those older than [`MAX_AGE`], and then the
+/// least recently fetched beyond [`MAX_STORED`].
+fn evicted(index: &Index, now: DateTime<Utc>) -> Vec<String> {
+    let mut by_age: Vec<(&String, &DateTime<Utc>)> = index.iter().collect();
+    by_age.sort_by_key(|(_, fetched_at)| std::cmp::Reverse(**fetched_at));
+    by_age
+        .into_iter()
+        .enumerate()
+        .filter(|(ix, (_, fetched_at))| *ix >= MAX_STORED || now - **fetched_at > MAX_AGE)
+        .map(|(_, (key, _))| key.clone())
+        .collect()
+}
+
+/// The index of entries in local storage. Entries stored before there was an index are indexed
+/// the first time it's loaded.
+fn load_index() -> Index {
+    if let Some(index) = storage_get(INDEX_KEY).and_then(|json| serde_json::from_str(&json).ok()) {
+        return index;
+    }
+    storage_keys()
+        .into_iter()
+        .filter(|key| key != INDEX_KEY)
+        .filter_map(|key| {
+            let entry: CachedResponse = serde_json::from_str(&storage_get(&key)?).ok()?;
+            Some((key, entry.fetched_at))
+        })
+        .collect()
+}
+
+fn save_index(index: &Index) {
+    if let Ok(json) = serde_json::to_string(index) {
+        storage_set(INDEX_KEY, &json);
+    }
+}
+
+fn storage_get(key: &str) -> Option<String> {
+    #[cfg(feature = "web")]
+    {
+        web_sys::window()?
+            .local_storage()
+            .ok()??
+            .get_item(&format!("{STORAGE_PREFIX}{key}"))
+            .ok()?
+    }
+    #[cfg(not(feature = "web"))]
+    {
+        let _ = key;
+        None
+    }
+}
+
+fn storage_set(key: &str, value: &str) {
+    #[cfg(feature = "web")]
+    if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
+        // e.g. the storage quota is exceeded; the in memory cache will do
+        if let Err(e) = storage.set_item(&format!("{STORAGE_PREFIX}{key}"), value) {
+            tracing::debug!("Failed to persist cached response for {key}: {e:?}");
+        }
+    }
+    #[cfg(not(feature = "web"))]
+    let _ = (key, value);
+}
+
+fn storage_remove(key: &str) {
+    #[cfg(feature = "web")]
+    if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
+        let _ = storage.remove_item(&format!("{STORAGE_PREFIX}{key}"));
+    }
+    #[cfg(not(feature = "web"))]
+    let _ = key;
+}
+
+/// Keys of every entry in local storage, without their prefix.
+fn storage_keys() -> Vec<String> {
+    #[cfg(feature = "web")]
+    {
+        let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) else {
+            return Vec::new();
+        };
+        let len = storage.length().unwrap_or(0);
+        (0..len)
+            .filter_map(|ix| storage.key(ix).ok().flatten())
+            .filter_map(|key| key.strip_prefix(STORAGE_PREFIX).map(String::from))
+            .collect()
+    }
+    #[cfg(not(feature = "web"))]
+    {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_old_and_excess_entries() {
+        let now = DateTime::from_timestamp(100 * 24 * 60 * 60, 0).unwrap();
+        let mut index: Index = (0..MAX_STORED)
+            .map(|ix| {
+                (
+                    format!("recent {ix}"),
+                    now - chrono::Duration::minutes(ix as i64),
+                )
+            })
+            .collect();
+        assert!(evicted(&index, now).is_empty());
+
+        index.insert("old".to_string(), now - MAX_AGE - chrono::Duration::days(1));
+        assert_eq!(evicted(&index, now), ["old"]);
+
+        index.insert("new".to_string(), now);
+        let oldest = format!("recent {}", MAX_STORED - 1);
+        assert_eq!(evicted(&index, now), [oldest, "old".to_string()]);
+    }
+}
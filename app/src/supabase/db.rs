@@ -1,20 +1,33 @@
-use std::fmt::Display;
+use std::{
+    cell::RefCell,
+    fmt::{Display, Write},
+    time::Duration,
+};
 
-use gloo_net::http::RequestBuilder;
-use serde::{de::DeserializeOwned, Serialize};
+use gloo_net::http::{Method, RequestBuilder, Response};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::{
-    conf::{SUPABASE_ANON_KEY, SUPABASE_API_URL},
-    utils,
-};
+use super::cache::{self, CachedResponse};
+use crate::{conf, t, utils};
 
 #[derive(Error, Debug)]
 pub enum Error {
     #[error(transparent)]
     Gloo(#[from] gloo_net::Error),
+    /// The request never got a response, e.g. when offline
+    #[error("Network error: {0}")]
+    Network(String),
+    /// The request was rejected for lack of (valid) credentials
+    #[error("Unauthorized (HTTP {status}): {error}")]
+    Unauthorized { status: u16, error: PostgrestError },
+    /// Any other error response
+    #[error("HTTP {status}: {error}")]
+    Http { status: u16, error: PostgrestError },
     #[error("Uh oh! We couldn't find today's pack!")]
     NoDailyPack,
+    #[error("No pack found with id {0} 🙈")]
+    NoPack(u64),
     #[allow(clippy::enum_variant_names)]
     #[error("{0}")]
     ErrorMessage(String),
@@ -37,61 +50,247 @@ impl Clone for Error {
     fn clone(&self) -> Self {
         match self {
             Self::Gloo(e) => Self::ErrorMessage(e.to_string()),
+            Self::Network(msg) => Self::Network(msg.clone()),
+            Self::Unauthorized { status, error } => Self::Unauthorized {
+                status: *status,
+                error: error.clone(),
+            },
+            Self::Http { status, error } => Self::Http {
+                status: *status,
+                error: error.clone(),
+            },
             Self::NoDailyPack => Self::NoDailyPack,
+            Self::NoPack(id) => Self::NoPack(*id),
             Self::ErrorMessage(msg) => Self::ErrorMessage(msg.clone()),
         }
     }
 }
 
+impl Error {
+    /// A message for the user in the current locale. Unexpected errors are left as is, since
+    /// they're mostly useful for bug reports.
+    pub fn localized(&self) -> String {
+        match self {
+            Self::NoDailyPack => t!("error-no-daily-pack"),
+            Self::NoPack(id) => t!("error-no-pack", id = *id),
+            Self::Network(_) => t!("error-network"),
+            Self::Unauthorized { .. } => t!("error-unauthorized"),
+            Self::Http { status, .. } if *status >= 500 => t!("error-server"),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Whether the request might succeed if tried again later.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Network(_) => true,
+            Self::Http { status, .. } => *status >= 500 || *status == 429,
+            _ => false,
+        }
+    }
+
+    pub(super) async fn from_response(rsp: Response) -> Self {
+        let status = rsp.status();
+        let body = rsp.text().await.unwrap_or_default();
+        // not every error comes from PostgREST, e.g. those from the API gateway
+        let error = serde_json::from_str(&body).unwrap_or_else(|_| PostgrestError {
+            message: Some(if body.is_empty() {
+                rsp.status_text()
+            } else {
+                body
+            }),
+            ..Default::default()
+        });
+        match status {
+            401 | 403 => Self::Unauthorized { status, error },
+            _ => Self::Http { status, error },
+        }
+    }
+}
+
+/// The body of a PostgREST error response, see
+/// <https://postgrest.org/en/stable/references/errors.html>.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+pub struct PostgrestError {
+    /// A PostgreSQL or PostgREST error code, e.g. "23505" or "PGRST116"
+    pub code: Option<String>,
+    pub message: Option<String>,
+    pub details: Option<String>,
+    pub hint: Option<String>,
+}
+
+impl Display for PostgrestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message.as_deref().unwrap_or("Unknown error"))?;
+        if let Some(code) = &self.code {
+            write!(f, " ({code})")?;
+        }
+        if let Some(details) = &self.details {
+            write!(f, ": {details}")?;
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, ". Hint: {hint}")?;
+        }
+        Ok(())
+    }
+}
+
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// The maximum number of rows Supabase returns per request.
+pub const MAX_ROWS: usize = 1000;
+
+/// How long to wait for a response before giving up (or retrying).
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many times idempotent requests are retried after transient failures.
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// The delay before the first retry, doubled for each subsequent retry.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+
+thread_local! {
+    static ACCESS_TOKEN: RefCell<Option<AccessToken>> = const { RefCell::new(None) };
+}
+
+/// The signed in user's access token, which authorizes requests as them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessToken {
+    pub token: String,
+    /// In seconds since the epoch
+    pub expires_at: i64,
+    /// Whose token it is
+    pub user_id: String,
+}
+
+/// Authorize requests as the signed in user from now on, rather than anonymously, so that row
+/// level security lets them see their own data. Once a user is no longer signed in, their cached
+/// responses are removed.
+pub fn set_access_token(token: Option<AccessToken>) {
+    let previous = ACCESS_TOKEN.with(|current| current.replace(token.clone()));
+    if let Some(previous) = previous {
+        if token.is_none_or(|token| token.user_id != previous.user_id) {
+            cache::remove_user(&previous.user_id);
+        }
+    }
+}
+
+/// The user's access token, unless it has expired or nobody is signed in.
+fn access_token() -> Option<AccessToken> {
+    let now = chrono::Utc::now().timestamp();
+    ACCESS_TOKEN.with(|token| {
+        token
+            .borrow()
+            .as_ref()
+            .filter(|token| now < token.expires_at)
+            .cloned()
+    })
+}
+
+/// The token to authorize requests with: the user's, unless it has expired, or the anon key.
+pub(super) fn bearer_token() -> String {
+    access_token()
+        .map(|token| token.token)
+        .unwrap_or_else(|| conf::get().supabase_anon_key.clone())
+}
+
 pub struct SupabaseRequest<T = serde_json::Value> {
-    builder: RequestBuilder,
+    method: Method,
+    url: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
     /// Gloonet annoyingly turns a RequestBuilder into a Request when setting the body, so we
-    /// postpone this until the end (to support adding filters before executing).
+    /// hold onto the parts and only build the request when executing it.
     body: Option<String>,
+    /// If set, responses are cached for this long (see [`cache`]).
+    cache_ttl: Option<Duration>,
+    /// The user the request is authorized as, if any, since row level security may give them a
+    /// different response than anyone else
+    user_id: Option<String>,
+    timeout: Duration,
+    /// How many times to retry after transient failures
+    retries: u32,
     _response_type: std::marker::PhantomData<T>,
 }
 
+// Derive would require `T: Clone`
+impl<T> Clone for SupabaseRequest<T> {
+    fn clone(&self) -> Self {
+        SupabaseRequest {
+            method: self.method.clone(),
+            url: self.url.clone(),
+            query: self.query.clone(),
+            headers: self.headers.clone(),
+            body: self.body.clone(),
+            cache_ttl: self.cache_ttl,
+            user_id: self.user_id.clone(),
+            timeout: self.timeout,
+            retries: self.retries,
+            _response_type: std::marker::PhantomData,
+        }
+    }
+}
+
 impl<T: DeserializeOwned> SupabaseRequest<T> {
-    /// Create a REST request _from_ the specified Supabase table/view.
-    pub fn from(table_name: &str) -> Self {
-        let builder = gloo_net::http::RequestBuilder::new(&format!(
-            "{SUPABASE_API_URL}/rest/v1/{table_name}"
-        ))
-        .header("apikey", SUPABASE_ANON_KEY)
-        .header("Authorization", &format!("Bearer {SUPABASE_ANON_KEY}"));
+    fn new(method: Method, path: impl Display) -> Self {
+        let config = conf::get();
+        let token = access_token();
+        let bearer = match &token {
+            Some(token) => token.token.clone(),
+            None => config.supabase_anon_key.clone(),
+        };
         SupabaseRequest {
-            builder,
+            method: method.clone(),
+            url: format!("{}/rest/v1/{path}", config.supabase_api_url),
+            query: Vec::new(),
+            headers: vec![
+                ("apikey".to_string(), config.supabase_anon_key.clone()),
+                ("Authorization".to_string(), format!("Bearer {bearer}")),
+            ],
             body: None,
+            cache_ttl: None,
+            user_id: token.map(|token| token.user_id),
+            timeout: DEFAULT_TIMEOUT,
+            // only requests that are safe to repeat
+            retries: if method == Method::GET {
+                DEFAULT_RETRIES
+            } else {
+                0
+            },
             _response_type: std::marker::PhantomData,
         }
     }
 
+    /// Create a REST request _from_ the specified Supabase table/view.
+    pub fn from(table_name: &str) -> Self {
+        Self::new(Method::GET, table_name)
+    }
+
     /// Call a Postgres function.
     pub fn rpc<F, U>(function: F, params: &U) -> Result<Self>
     where
         F: Display,
         U: Serialize,
     {
-        let builder = gloo_net::http::RequestBuilder::new(&format!(
-            "{SUPABASE_API_URL}/rest/v1/rpc/{function}"
-        ))
-        .header("apikey", SUPABASE_ANON_KEY)
-        .header("Authorization", &format!("Bearer {SUPABASE_ANON_KEY}"));
         let json = serde_json::to_string(params).map_err(gloo_net::Error::from)?;
         Ok(SupabaseRequest {
-            builder,
             body: Some(json),
-            _response_type: std::marker::PhantomData,
+            ..Self::new(Method::POST, format_args!("rpc/{function}"))
         })
     }
 
     /// If doing some join or complex query, this can be used to manually cast to the expected type.
     pub fn cast<V>(self) -> SupabaseRequest<V> {
         SupabaseRequest {
-            builder: self.builder,
+            method: self.method,
+            url: self.url,
+            query: self.query,
+            headers: self.headers,
             body: self.body,
+            cache_ttl: self.cache_ttl,
+            user_id: self.user_id,
+            timeout: self.timeout,
+            retries: self.retries,
             _response_type: std::marker::PhantomData,
         }
     }
@@ -101,60 +300,785 @@ impl<T: DeserializeOwned> SupabaseRequest<T> {
         self.cast()
     }
 
+    /// Cache responses for `ttl`, after which they're revalidated.
+    pub fn cached(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Give up on responses that take longer than `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Retry up to `retries` times after transient failures, with exponential backoff. Only GET
+    /// requests are retried by default, so only set this on other requests if they're idempotent.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Set a header, replacing any previous value
+    fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
     /// Select columns
     pub fn select<C>(mut self, columns: C) -> Self
     where
         C: AsRef<str>,
     {
-        self.builder = self.builder.query([("select", columns)]);
+        self.query
+            .push(("select".to_string(), columns.as_ref().to_string()));
+        self
+    }
+
+    /// Select columns of a related resource, e.g. `embed("sounds", "path,default_")`. Filters on
+    /// its columns can then be added as e.g. "sounds.default_".
+    pub fn embed<R, C>(mut self, resource: R, columns: C) -> Self
+    where
+        R: AsRef<str>,
+        C: AsRef<str>,
+    {
+        let embedded = format!("{}({})", resource.as_ref(), columns.as_ref());
+        match self.query.iter_mut().find(|(k, _)| k == "select") {
+            Some((_, select)) => {
+                select.push(',');
+                select.push_str(&embedded);
+            }
+            None => self
+                .query
+                .push(("select".to_string(), format!("*,{embedded}"))),
+        }
+        self
+    }
+
+    /// Add a filter
+    pub fn filter(mut self, filter: Filter) -> Self {
+        let condition = filter.condition();
+        self.query.push((filter.column, condition));
         self
     }
 
     /// Add equality filter
-    pub fn eq<C, D>(mut self, column: C, filter: D) -> Self
+    pub fn eq<C, D>(self, column: C, value: D) -> Self
     where
         C: AsRef<str>,
         D: Display,
     {
-        self.builder = self
-            .builder
-            .query([(column.as_ref(), &format!("eq.{}", filter))]);
-        self
+        self.filter(Filter::eq(column, value))
+    }
+
+    /// Add inequality filter
+    pub fn neq<C, D>(self, column: C, value: D) -> Self
+    where
+        C: AsRef<str>,
+        D: Display,
+    {
+        self.filter(Filter::neq(column, value))
+    }
+
+    /// Add greater than filter
+    pub fn gt<C, D>(self, column: C, value: D) -> Self
+    where
+        C: AsRef<str>,
+        D: Display,
+    {
+        self.filter(Filter::gt(column, value))
+    }
+
+    /// Add greater than or equal filter
+    pub fn gte<C, D>(self, column: C, value: D) -> Self
+    where
+        C: AsRef<str>,
+        D: Display,
+    {
+        self.filter(Filter::gte(column, value))
+    }
+
+    /// Add less than filter
+    pub fn lt<C, D>(self, column: C, value: D) -> Self
+    where
+        C: AsRef<str>,
+        D: Display,
+    {
+        self.filter(Filter::lt(column, value))
+    }
+
+    /// Add less than or equal filter
+    pub fn lte<C, D>(self, column: C, value: D) -> Self
+    where
+        C: AsRef<str>,
+        D: Display,
+    {
+        self.filter(Filter::lte(column, value))
+    }
+
+    /// Add case sensitive pattern filter, using `*` as the wildcard
+    pub fn like<C, P>(self, column: C, pattern: P) -> Self
+    where
+        C: AsRef<str>,
+        P: Display,
+    {
+        self.filter(Filter::like(column, pattern))
+    }
+
+    /// Add case insensitive pattern filter, using `*` as the wildcard
+    pub fn ilike<C, P>(self, column: C, pattern: P) -> Self
+    where
+        C: AsRef<str>,
+        P: Display,
+    {
+        self.filter(Filter::ilike(column, pattern))
+    }
+
+    /// Add IS filter, e.g. for null checks
+    pub fn is<C>(self, column: C, value: Is) -> Self
+    where
+        C: AsRef<str>,
+    {
+        self.filter(Filter::is(column, value))
     }
 
     /// Add IN array filter
-    pub fn in_<C, I, D>(mut self, column: C, values: I) -> Self
+    pub fn in_<C, I, D>(self, column: C, values: I) -> Self
     where
         C: AsRef<str>,
         I: IntoIterator<Item = D>,
         D: Display,
     {
-        self.builder = self.builder.query([(
-            column.as_ref(),
-            &format!("in.({})", utils::join(values, ",")),
-        )]);
+        self.filter(Filter::in_(column, values))
+    }
+
+    /// Add full text search filter. The `config` is the text search configuration, e.g.
+    /// "english".
+    pub fn fts<C, Q>(self, column: C, query: Q, config: Option<&str>) -> Self
+    where
+        C: AsRef<str>,
+        Q: Display,
+    {
+        self.filter(Filter::fts(column, query, config))
+    }
+
+    /// Match any of the filters
+    pub fn or<I>(mut self, filters: I) -> Self
+    where
+        I: IntoIterator<Item = Filter>,
+    {
+        self.query
+            .push(("or".to_string(), format!("({})", utils::join(filters, ","))));
+        self
+    }
+
+    /// Limit the number of rows returned
+    pub fn limit(mut self, count: usize) -> Self {
+        self.query.push(("limit".to_string(), count.to_string()));
+        self
+    }
+
+    /// Skip the first `count` rows
+    pub fn offset(mut self, count: usize) -> Self {
+        self.query.push(("offset".to_string(), count.to_string()));
+        self
+    }
+
+    /// Only return rows `from` through `to` (zero based, inclusive)
+    pub fn range(self, from: usize, to: usize) -> Self {
+        self.header("Range-Unit", "items")
+            .header("Range", format!("{from}-{to}"))
+    }
+
+    /// Order by a column. Call again to break ties with further columns.
+    pub fn order<C>(mut self, column: C, ascending: bool) -> Self
+    where
+        C: AsRef<str>,
+    {
+        let direction = if ascending { "asc" } else { "desc" };
+        let order = format!("{}.{direction}", column.as_ref());
+        match self.query.iter_mut().find(|(k, _)| k == "order") {
+            Some((_, orders)) => {
+                orders.push(',');
+                orders.push_str(&order);
+            }
+            None => self.query.push(("order".to_string(), order)),
+        }
         self
     }
 
     /// Execute request
     pub async fn execute(self) -> Result<T, Error> {
-        let req = if let Some(body) = self.body {
-            self.builder.body(body)
+        let rsp = self.execute_raw().await?;
+        Ok(serde_json::from_str(&rsp.body).map_err(gloo_net::Error::from)?)
+    }
+
+    /// Execute request without deserializing the response
+    async fn execute_raw(self) -> Result<CachedResponse> {
+        match self.cache_ttl {
+            Some(ttl) if self.method == Method::GET => self.execute_cached(ttl).await,
+            _ => read_response(self.send(None).await?).await,
+        }
+    }
+
+    async fn execute_cached(self, ttl: Duration) -> Result<CachedResponse> {
+        let key = self.cache_key();
+        let cached = cache::get(&key);
+        if let Some(cached) = cached.clone().filter(|c| c.is_fresh(ttl)) {
+            return Ok(cached);
+        }
+
+        let etag = cached.as_ref().and_then(|c| c.etag.clone());
+        match (self.send(etag.as_deref()).await, cached) {
+            (Ok(rsp), Some(mut cached)) if rsp.status() == 304 => {
+                cached.touch();
+                cache::put(&key, cached.clone());
+                Ok(cached)
+            }
+            (Ok(rsp), _) => {
+                let entry = read_response(rsp).await?;
+                cache::put(&key, entry.clone());
+                Ok(entry)
+            }
+            // Better stale than nothing, e.g. when offline
+            (Err(e), Some(cached)) if e.is_transient() => {
+                tracing::warn!("Serving stale response for {key}: {e}");
+                Ok(cached)
+            }
+            (Err(e), _) => Err(e),
+        }
+    }
+
+    /// Send the request, retrying after transient failures.
+    async fn send(&self, if_none_match: Option<&str>) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            match self.send_once(if_none_match).await {
+                Err(e) if e.is_transient() && attempt < self.retries => {
+                    let delay = retry_delay(attempt);
+                    tracing::debug!("Retrying {} in {delay:?}: {e}", self.url);
+                    #[cfg(feature = "web")]
+                    async_std::task::sleep(delay).await;
+                    #[cfg(not(feature = "web"))]
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Send the request, failing on error responses.
+    async fn send_once(&self, if_none_match: Option<&str>) -> Result<Response> {
+        let mut builder = RequestBuilder::new(&self.url)
+            .method(self.method.clone())
+            .query(self.query.iter().map(|(k, v)| (k.as_str(), v)));
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(etag) = if_none_match {
+            builder = builder.header("If-None-Match", etag);
+        }
+        let req = if let Some(body) = &self.body {
+            builder
+                .header("Content-Type", "application/json")
+                .body(body.as_str())
         } else {
-            self.builder.build()
+            builder.build()
         }?;
-        let rsp = req.send().await?.json().await?;
-        Ok(rsp)
+        #[cfg(feature = "web")]
+        let sent = async_std::future::timeout(self.timeout, req.send())
+            .await
+            .map_err(|_| Error::Network(format!("Timed out after {:?}", self.timeout)))?;
+        #[cfg(not(feature = "web"))]
+        let sent = tokio::time::timeout(self.timeout, req.send())
+            .await
+            .map_err(|_| Error::Network(format!("Timed out after {:?}", self.timeout)))?;
+        let rsp = sent.map_err(|e| Error::Network(e.to_string()))?;
+        if rsp.ok() || rsp.status() == 304 {
+            Ok(rsp)
+        } else {
+            Err(Error::from_response(rsp).await)
+        }
+    }
+
+    /// The query string, before URL encoding.
+    fn query_string(&self) -> String {
+        utils::join(self.query.iter().map(|(k, v)| format!("{k}={v}")), "&")
+    }
+
+    /// The full request URL, any headers that change the response and the user it's authorized as,
+    /// which identify cached responses.
+    fn cache_key(&self) -> String {
+        let mut key = format!("{}?{}", self.url, self.query_string());
+        for (name, value) in &self.headers {
+            if ["Range", "Prefer"].contains(&name.as_str()) {
+                write!(key, " {name}: {value}").unwrap();
+            }
+        }
+        if let Some(user_id) = &self.user_id {
+            write!(key, " as {user_id}").unwrap();
+        }
+        key
+    }
+}
+
+/// Exponential backoff with full jitter, i.e. a random delay of up to
+/// `RETRY_BASE_DELAY * 2^attempt`, so that clients don't retry in lockstep.
+fn retry_delay(attempt: u32) -> Duration {
+    let max = RETRY_BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt));
+    max.mul_f64(rand::random::<f64>())
+}
+
+async fn read_response(rsp: Response) -> Result<CachedResponse> {
+    let headers = rsp.headers();
+    Ok(CachedResponse::new(
+        rsp.text().await?,
+        headers.get("etag"),
+        headers.get("content-range"),
+    ))
+}
+
+/// A PostgREST filter on a column, e.g. `id=eq.1`. Filters are usually added with the methods
+/// of the same name on [`SupabaseRequest`], but can also be combined with [`SupabaseRequest::or`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    column: String,
+    /// e.g. "eq" or "not.in"
+    operator: String,
+    value: String,
+    /// Whether the value is a parenthesized list, which is already quoted
+    list: bool,
+}
+
+impl Filter {
+    fn new<C>(column: C, operator: &str, value: impl Display) -> Self
+    where
+        C: AsRef<str>,
+    {
+        Self {
+            column: column.as_ref().to_string(),
+            operator: operator.to_string(),
+            value: value.to_string(),
+            list: false,
+        }
+    }
+
+    pub fn eq<C: AsRef<str>>(column: C, value: impl Display) -> Self {
+        Self::new(column, "eq", value)
+    }
+
+    pub fn neq<C: AsRef<str>>(column: C, value: impl Display) -> Self {
+        Self::new(column, "neq", value)
+    }
+
+    pub fn gt<C: AsRef<str>>(column: C, value: impl Display) -> Self {
+        Self::new(column, "gt", value)
+    }
+
+    pub fn gte<C: AsRef<str>>(column: C, value: impl Display) -> Self {
+        Self::new(column, "gte", value)
+    }
+
+    pub fn lt<C: AsRef<str>>(column: C, value: impl Display) -> Self {
+        Self::new(column, "lt", value)
+    }
+
+    pub fn lte<C: AsRef<str>>(column: C, value: impl Display) -> Self {
+        Self::new(column, "lte", value)
+    }
+
+    pub fn like<C: AsRef<str>>(column: C, pattern: impl Display) -> Self {
+        Self::new(column, "like", pattern)
+    }
+
+    pub fn ilike<C: AsRef<str>>(column: C, pattern: impl Display) -> Self {
+        Self::new(column, "ilike", pattern)
+    }
+
+    pub fn is<C: AsRef<str>>(column: C, value: Is) -> Self {
+        Self::new(column, "is", value)
+    }
+
+    pub fn in_<C, I, D>(column: C, values: I) -> Self
+    where
+        C: AsRef<str>,
+        I: IntoIterator<Item = D>,
+        D: Display,
+    {
+        let values = values.into_iter().map(|v| quote(&v.to_string()));
+        Self {
+            list: true,
+            ..Self::new(column, "in", format_args!("({})", utils::join(values, ",")))
+        }
+    }
+
+    pub fn fts<C: AsRef<str>>(column: C, query: impl Display, config: Option<&str>) -> Self {
+        match config {
+            Some(config) => Self::new(column, &format!("fts({config})"), query),
+            None => Self::new(column, "fts", query),
+        }
+    }
+
+    /// Negate the filter
+    pub fn not(mut self) -> Self {
+        self.operator = format!("not.{}", self.operator);
+        self
+    }
+
+    /// The query parameter value, e.g. "eq.1"
+    fn condition(&self) -> String {
+        format!("{}.{}", self.operator, self.value)
+    }
+}
+
+/// The filter as part of a logical operator, e.g. "id.eq.1" in `or=(id.eq.1,id.eq.2)`.
+impl Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = if self.list {
+            self.value.clone()
+        } else {
+            quote(&self.value)
+        };
+        write!(f, "{}.{}.{}", self.column, self.operator, value)
+    }
+}
+
+/// Values for [`Filter::is`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Is {
+    Null,
+    True,
+    False,
+    Unknown,
+}
+
+impl Display for Is {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Null => "null",
+            Self::True => "true",
+            Self::False => "false",
+            Self::Unknown => "unknown",
+        })
+    }
+}
+
+/// Double quote values containing characters reserved by PostgREST in lists and logical
+/// operators.
+fn quote(value: &str) -> String {
+    if value.contains([',', '.', ':', '(', ')', '"', '\\', ' ']) {
+        let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+        format!("\"{escaped}\"")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Declare the columns of a [`SupabaseResource`], so they can be used in filters without typos.
+///
+/// ```ignore
+/// columns! {
+///     pub enum BirdColumn {
+///         Id => "id",
+///         CommonName => "common_name",
+///     }
+/// }
+/// ```
+macro_rules! columns {
+    ($(#[$meta:meta])* $vis:vis enum $name:ident { $($variant:ident => $column:literal),* $(,)? }) => {
+        $(#[$meta])*
+        // not every column is filtered on
+        #[allow(dead_code)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $($variant),*
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                match self {
+                    $(Self::$variant => $column),*
+                }
+            }
+        }
+    };
+}
+pub(crate) use columns;
+
+/// A page of rows.
+#[derive(Debug, Clone)]
+pub struct Page<R> {
+    pub rows: Vec<R>,
+    /// The total number of matching rows, if known
+    pub total: Option<usize>,
+}
+
+impl<R: DeserializeOwned> SupabaseRequest<Vec<R>> {
+    /// Execute request, also counting the total number of matching rows.
+    pub async fn execute_page(self) -> Result<Page<R>> {
+        let rsp = self.header("Prefer", "count=exact").execute_raw().await?;
+        Ok(Page {
+            rows: serde_json::from_str(&rsp.body).map_err(gloo_net::Error::from)?,
+            total: rsp.content_range.as_deref().and_then(content_range_total),
+        })
+    }
+
+    /// Walk through the results `page_size` rows at a time. Make sure to [`Self::order`] the
+    /// results so that pages are stable.
+    pub fn pages(self, page_size: usize) -> Pages<R> {
+        Pages {
+            request: self,
+            page_size: page_size.clamp(1, MAX_ROWS),
+            offset: 0,
+            total: None,
+            done: false,
+        }
+    }
+}
+
+/// The total from a `Content-Range` header, e.g. 3573 for "0-24/3573".
+fn content_range_total(header: &str) -> Option<usize> {
+    header.rsplit_once('/')?.1.parse().ok()
+}
+
+/// An async iterator over pages of a request, see [`SupabaseRequest::pages`].
+pub struct Pages<R> {
+    request: SupabaseRequest<Vec<R>>,
+    page_size: usize,
+    offset: usize,
+    total: Option<usize>,
+    done: bool,
+}
+
+impl<R: DeserializeOwned> Pages<R> {
+    /// Fetch the next page, or `None` once all rows have been fetched. Failed pages can be
+    /// retried by calling this again.
+    pub async fn next_page(&mut self) -> Option<Result<Vec<R>>> {
+        if self.done {
+            return None;
+        }
+        let page = self
+            .request
+            .clone()
+            .limit(self.page_size)
+            .offset(self.offset)
+            .execute_page()
+            .await;
+        Some(page.map(|page| {
+            self.offset += page.rows.len();
+            self.total = page.total.or(self.total);
+            self.done = page.rows.len() < self.page_size
+                || self.total.is_some_and(|total| self.offset >= total);
+            page.rows
+        }))
+    }
+
+    /// The total number of rows, once known
+    pub fn total(&self) -> Option<usize> {
+        self.total
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Fetch all of the remaining rows.
+    pub async fn collect_all(mut self) -> Result<Vec<R>> {
+        let mut rows = Vec::new();
+        while let Some(page) = self.next_page().await {
+            rows.extend(page?);
+        }
+        Ok(rows)
     }
 }
 
 /// Indicates a type corresponds to a RESTful resource, i.e. a table in Supabase.
 pub trait SupabaseResource: Sized + DeserializeOwned {
+    /// The columns that can be filtered on, see [`columns!`].
+    type Column: AsRef<str> + Copy;
+
     fn table_name() -> &'static str;
 
+    /// How long responses may be served from the client side cache without revalidation. By
+    /// default, responses aren't cached.
+    fn cache_ttl() -> Option<Duration> {
+        None
+    }
+
     // TODO: When auth is implemented, there will probably be some state to reference the correct
     // auth token.
     fn request() -> SupabaseRequest<Vec<Self>> {
-        SupabaseRequest::from(Self::table_name())
+        let request = SupabaseRequest::from(Self::table_name());
+        match Self::cache_ttl() {
+            Some(ttl) => request.cached(ttl),
+            None => request,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_content_range_totals() {
+        assert_eq!(content_range_total("0-24/3573"), Some(3573));
+        assert_eq!(content_range_total("*/0"), Some(0));
+        assert_eq!(content_range_total("0-24/*"), None);
+    }
+
+    #[test]
+    fn displays_postgrest_errors() {
+        let error: PostgrestError = serde_json::from_str(
+            r#"{"code":"PGRST116","details":"The result contains 0 rows","hint":null,"message":"JSON object requested, multiple (or no) rows returned"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Error::Http { status: 406, error }.to_string(),
+            "HTTP 406: JSON object requested, multiple (or no) rows returned (PGRST116): The \
+            result contains 0 rows"
+        );
+        assert_eq!(
+            Error::Unauthorized {
+                status: 401,
+                error: PostgrestError {
+                    message: Some("JWT expired".to_string()),
+                    code: Some("PGRST301".to_string()),
+                    ..Default::default()
+                },
+            }
+            .clone()
+            .to_string(),
+            "Unauthorized (HTTP 401): JWT expired (PGRST301)"
+        );
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        for attempt in 0..5 {
+            let delay = retry_delay(attempt);
+            assert!(delay <= RETRY_BASE_DELAY * 2u32.pow(attempt), "{delay:?}");
+        }
+    }
+
+    #[test]
+    fn caches_responses_per_user() {
+        let key = || SupabaseRequest::<()>::from("packs").cache_key();
+        let anonymous = key();
+        set_access_token(Some(AccessToken {
+            token: "token".to_string(),
+            expires_at: i64::MAX,
+            user_id: "4d1f".to_string(),
+        }));
+        let signed_in = key();
+        set_access_token(None);
+        assert_ne!(anonymous, signed_in);
+        assert!(signed_in.ends_with(" as 4d1f"));
+    }
+
+    fn query(request: SupabaseRequest) -> String {
+        request.query_string()
+    }
+
+    #[test]
+    fn builds_comparison_filters() {
+        let request = SupabaseRequest::from("birds")
+            .select("*")
+            .eq("id", 1)
+            .neq("common_name", "Northern Cardinal")
+            .gt("difficulty", 0.25)
+            .gte("id", 10)
+            .lt("id", 20)
+            .lte("difficulty", 0.75);
+        assert_eq!(
+            query(request),
+            "select=*&id=eq.1&common_name=neq.Northern Cardinal&difficulty=gt.0.25&id=gte.10\
+            &id=lt.20&difficulty=lte.0.75"
+        );
+    }
+
+    #[test]
+    fn builds_pattern_and_is_filters() {
+        let request = SupabaseRequest::from("birds")
+            .like("scientific_name", "Cardinalis*")
+            .ilike("common_name", "*cardinal*")
+            .is("family", Is::Null)
+            .filter(Filter::is("image", Is::Null).not());
+        assert_eq!(
+            query(request),
+            "scientific_name=like.Cardinalis*&common_name=ilike.*cardinal*&family=is.null\
+            &image=not.is.null"
+        );
+    }
+
+    #[test]
+    fn builds_in_filters_with_quoting() {
+        let request = SupabaseRequest::from("birds")
+            .in_("id", [1, 2, 3])
+            .in_("common_name", ["Robin", "Cardinal, Northern"]);
+        assert_eq!(
+            query(request),
+            r#"id=in.(1,2,3)&common_name=in.(Robin,"Cardinal, Northern")"#
+        );
+    }
+
+    #[test]
+    fn builds_full_text_search() {
+        let request = SupabaseRequest::from("birds")
+            .fts("common_name", "cardinal", None)
+            .fts("description", "red'bird", Some("english"));
+        assert_eq!(
+            query(request),
+            "common_name=fts.cardinal&description=fts(english).red'bird"
+        );
+    }
+
+    #[test]
+    fn builds_or_filters() {
+        let request = SupabaseRequest::from("birds").or([
+            Filter::eq("common_name", "Blue Jay"),
+            Filter::ilike("scientific_name", "cardinalis*"),
+            Filter::in_("id", [1, 2]),
+            Filter::gt("difficulty", 0.5).not(),
+        ]);
+        assert_eq!(
+            query(request),
+            r#"or=(common_name.eq."Blue Jay",scientific_name.ilike.cardinalis*,id.in.(1,2),difficulty.not.gt."0.5")"#
+        );
+    }
+
+    #[test]
+    fn builds_orders_and_embeds() {
+        let request = SupabaseRequest::from("packs")
+            .select("id,name")
+            .embed("birds", "id,common_name")
+            .order("name", true)
+            .order("id", false)
+            .limit(10)
+            .offset(20);
+        assert_eq!(
+            query(request),
+            "select=id,name,birds(id,common_name)&order=name.asc,id.desc&limit=10&offset=20"
+        );
+
+        let request = SupabaseRequest::from("packs").embed("birds", "*");
+        assert_eq!(query(request), "select=*,birds(*)");
+    }
+
+    columns! {
+        enum TestColumn {
+            Id => "id",
+            CommonName => "common_name",
+        }
+    }
+
+    #[test]
+    fn accepts_typed_columns() {
+        let request = SupabaseRequest::from("birds")
+            .eq(TestColumn::Id, 1)
+            .order(TestColumn::CommonName, true);
+        assert_eq!(query(request), "id=eq.1&order=common_name.asc");
     }
 }
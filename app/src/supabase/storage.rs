@@ -1,8 +1,258 @@
-use crate::conf::SUPABASE_API_URL;
+//! URLs of media in Supabase storage, signed since buckets aren't public, optionally resized by
+//! Supabase's image transformations and served by a CDN.
 
-pub fn storage_object_url<S: AsRef<str>>(path: S) -> String {
-    format!(
-        "{SUPABASE_API_URL}/storage/v1/object/public/{}",
-        path.as_ref()
-    )
+use std::{collections::HashMap, time::Duration};
+
+use gloo_net::http::Request;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{Error, Result};
+use crate::{conf, utils};
+
+/// How long signed URLs are valid for by default.
+pub const SIGNED_URL_EXPIRY: Duration = Duration::from_secs(60 * 60);
+
+/// Resizing and compression applied to an image before it's served, see
+/// <https://supabase.com/docs/guides/storage/serving/image-transformations>.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct ImageTransform {
+    /// Width in pixels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    /// Height in pixels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// From 20 to 100, defaults to 80
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resize: Option<Resize>,
+}
+
+/// How an image is fit into both a width and height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Resize {
+    /// Fill the dimensions, cropping as needed (the default)
+    Cover,
+    /// Fit within the dimensions, keeping the aspect ratio
+    Contain,
+    /// Stretch to the dimensions
+    Fill,
+}
+
+impl Resize {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Cover => "cover",
+            Self::Contain => "contain",
+            Self::Fill => "fill",
+        }
+    }
+}
+
+impl ImageTransform {
+    pub fn width(width: u32) -> Self {
+        Self {
+            width: Some(width),
+            ..Default::default()
+        }
+    }
+
+    pub fn quality(mut self, quality: u8) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
+    fn query_string(&self) -> String {
+        let params = [
+            self.width.map(|w| format!("width={w}")),
+            self.height.map(|h| format!("height={h}")),
+            self.quality.map(|q| format!("quality={q}")),
+            self.resize.map(|r| format!("resize={}", r.as_str())),
+        ];
+        utils::join(params.into_iter().flatten(), "&")
+    }
+}
+
+/// The storage API, e.g. http://127.0.0.1:54321/storage/v1
+pub fn storage_url() -> String {
+    let config = conf::get();
+    config
+        .supabase_storage_url
+        .clone()
+        .unwrap_or_else(|| format!("{}/storage/v1", config.supabase_api_url))
+}
+
+/// Where media is served from: the CDN if there is one, otherwise the storage API.
+fn media_url() -> String {
+    conf::get()
+        .storage_cdn_url
+        .clone()
+        .unwrap_or_else(storage_url)
+}
+
+/// URL an object is served at once signed (see [`sign`]), but without a token, e.g. for
+/// `bird_images/cardinalis-cardinalis/unlicensed-optimized.jpg`. Storage refuses it, but the
+/// service worker serves any copy it cached of the signed object, so it's a fallback for when
+/// media can't be signed, e.g. offline.
+pub fn unsigned_url<S: AsRef<str>>(path: S, transform: Option<&ImageTransform>) -> String {
+    match transform {
+        Some(transform) => format!(
+            "{}/render/image/sign/{}?{}",
+            media_url(),
+            path.as_ref(),
+            transform.query_string()
+        ),
+        None => format!("{}/object/sign/{}", media_url(), path.as_ref()),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignRequest<'a> {
+    expires_in: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transform: Option<&'a ImageTransform>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignAllRequest<'a> {
+    expires_in: u64,
+    paths: Vec<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct Signed {
+    #[serde(rename = "signedURL")]
+    signed_url: Option<String>,
+    /// Only set when signing several objects
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Sign the URL of an object (and optionally transform it, if it's an image), so that it can be
+/// fetched for `expires_in` even though its bucket isn't public. The transformation is signed
+/// with the token, and also added to the URL so that the service worker can tell sizes apart.
+pub async fn sign<S: AsRef<str>>(
+    path: S,
+    expires_in: Duration,
+    transform: Option<&ImageTransform>,
+) -> Result<String> {
+    let body = SignRequest {
+        expires_in: expires_in.as_secs(),
+        transform,
+    };
+    let url = format!("{}/object/sign/{}", storage_url(), path.as_ref());
+    let signed: Signed = post(&url, &body).await?;
+    let url = signed_url(signed)?;
+    Ok(match transform {
+        Some(transform) => format!("{url}&{}", transform.query_string()),
+        None => url,
+    })
+}
+
+/// Sign the URLs of several objects (see [`sign`]), returning signed URLs by path. Objects that
+/// can't be signed, e.g. those the user may not read, are left out.
+pub async fn sign_all<I, S>(paths: I, expires_in: Duration) -> Result<HashMap<String, String>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let paths: Vec<S> = paths.into_iter().collect();
+    // objects are signed per bucket
+    let mut by_bucket: HashMap<&str, Vec<&str>> = HashMap::new();
+    for path in &paths {
+        let (bucket, name) = path
+            .as_ref()
+            .split_once('/')
+            .ok_or_else(|| format!("Storage path {:?} has no bucket", path.as_ref()))?;
+        by_bucket.entry(bucket).or_default().push(name);
+    }
+    let mut urls = HashMap::new();
+    for (bucket, names) in by_bucket {
+        let body = SignAllRequest {
+            expires_in: expires_in.as_secs(),
+            paths: names,
+        };
+        let url = format!("{}/object/sign/{bucket}", storage_url());
+        let signed: Vec<Signed> = post(&url, &body).await?;
+        for signed in signed {
+            let path = format!("{bucket}/{}", signed.path.clone().unwrap_or_default());
+            match signed_url(signed) {
+                Ok(url) => {
+                    urls.insert(path, url);
+                }
+                Err(e) => tracing::debug!("{e}"),
+            }
+        }
+    }
+    Ok(urls)
+}
+
+fn signed_url(signed: Signed) -> Result<String> {
+    match signed {
+        Signed {
+            signed_url: Some(url),
+            ..
+        } => Ok(format!("{}{url}", media_url())),
+        Signed { error, path, .. } => Err(Error::ErrorMessage(format!(
+            "Failed to sign {}: {}",
+            path.unwrap_or_default(),
+            error.unwrap_or_else(|| "no URL returned".to_string())
+        ))),
+    }
+}
+
+async fn post<B: Serialize, R: DeserializeOwned>(url: &str, body: &B) -> Result<R> {
+    let req = Request::post(url)
+        .header("apikey", &conf::get().supabase_anon_key)
+        .header(
+            "Authorization",
+            &format!("Bearer {}", super::bearer_token()),
+        )
+        .json(body)?;
+    #[cfg(feature = "web")]
+    let sent = async_std::future::timeout(super::DEFAULT_TIMEOUT, req.send())
+        .await
+        .map_err(|_| Error::Network(format!("Timed out after {:?}", super::DEFAULT_TIMEOUT)))?;
+    #[cfg(not(feature = "web"))]
+    let sent = tokio::time::timeout(super::DEFAULT_TIMEOUT, req.send())
+        .await
+        .map_err(|_| Error::Network(format!("Timed out after {:?}", super::DEFAULT_TIMEOUT)))?;
+    let rsp = sent.map_err(|e| Error::Network(e.to_string()))?;
+    if !rsp.ok() {
+        return Err(Error::from_response(rsp).await);
+    }
+    Ok(rsp.json().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_transform_query_strings() {
+        assert_eq!(ImageTransform::default().query_string(), "");
+        assert_eq!(
+            ImageTransform::width(192).quality(75).query_string(),
+            "width=192&quality=75"
+        );
+        let transform = ImageTransform {
+            height: Some(100),
+            resize: Some(Resize::Contain),
+            ..ImageTransform::width(200)
+        };
+        assert_eq!(
+            transform.query_string(),
+            "width=200&height=100&resize=contain"
+        );
+        assert_eq!(
+            serde_json::to_string(&transform).unwrap(),
+            r#"{"width":200,"height":100,"resize":"contain"}"#
+        );
+    }
 }
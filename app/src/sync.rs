@@ -1,7 +1,10 @@
 //! Synchronization logic
 //!
 //! Immediate state is helded in local storage signals, with async read/writes to remote DB at
-//! certain times.
+//! certain times. Changes made while offline are queued and pushed once we're back online.
+//!
+//! Games played while signed in are also started by the server, and queued for it to record once
+//! they're finished, see [`crate::server`], since stats pushed from here can't be trusted.
 use std::ops::{Deref, DerefMut};
 
 use chrono::{DateTime, Utc};
@@ -10,8 +13,12 @@ use dioxus_sdk::storage::{use_synced_storage, LocalStorage};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    datasource,
+    offline::ONLINE,
+    pack::PackIdentifier,
+    server::{GameRecord, GameSession},
     stats::Stats,
-    supabase::{AuthState, Result, SupabaseRequest, SupabaseResource},
+    supabase::{AuthState, Result},
 };
 
 #[derive(Clone)]
@@ -20,6 +27,14 @@ pub struct Sync<T: 'static> {
     // remote: Source<T>,
     /// Storage backed state
     local: Signal<T>,
+    /// Whether there are local changes that haven't been pushed to the remote DB yet, e.g.
+    /// because we're offline. Persisted so that changes aren't lost when closing the app.
+    pending: Signal<bool>,
+    /// Finished games that the server hasn't recorded yet. Only used with the `fullstack`
+    /// feature, since otherwise there's no server.
+    games: Signal<Vec<GameRecord>>,
+    /// Whether queued games are being sent to the server
+    recording: CopyValue<bool>,
     /// Future to sync remote and local
     fut: UseFuture,
     /// Auth state
@@ -31,6 +46,22 @@ impl<T: 'static + std::clone::Clone> Copy for Sync<T> {}
 impl Sync<Stats> {
     pub fn init(auth: AuthState) -> Self {
         let local = use_synced_storage::<LocalStorage, _>("stats".to_string(), Stats::default);
+        let mut pending =
+            use_synced_storage::<LocalStorage, _>("stats_pending".to_string(), || false);
+        let games = use_synced_storage::<LocalStorage, _>("games_pending".to_string(), Vec::new);
+        let recording = use_hook(|| CopyValue::new(false));
+
+        // Queue any changes made after loading
+        let mut loaded = use_signal(|| false);
+        use_effect(move || {
+            local.read();
+            if !*loaded.peek() {
+                loaded.set(true);
+            } else if !*pending.peek() {
+                pending.set(true);
+            }
+        });
+
         let fut = use_future(move || async move {
             // loop {
             // sync stuff
@@ -39,7 +70,41 @@ impl Sync<Stats> {
             // }
         });
 
-        Self { local, fut, auth }
+        let this = Self {
+            local,
+            pending,
+            games,
+            recording,
+            fut,
+            auth,
+        };
+
+        // Push queued changes whenever we're (back) online
+        use_effect(move || {
+            if pending() && ONLINE() && auth.is_logged_in() {
+                spawn(async move {
+                    let mut this = this;
+                    if let Err(e) = this.sync().await {
+                        tracing::warn!("Failed to sync stats: {e}");
+                    }
+                });
+            }
+        });
+
+        // Send queued games to the server whenever we're (back) online
+        #[cfg(feature = "fullstack")]
+        use_effect(move || {
+            if !games.read().is_empty() && ONLINE() && auth.is_logged_in() && !*recording.peek() {
+                spawn(async move {
+                    let mut this = this;
+                    if let Err(e) = this.record_games().await {
+                        tracing::warn!("Failed to record games: {e}");
+                    }
+                });
+            }
+        });
+
+        this
         // Super quick "sync" hack: just push whenever user logs in.
         // use_memo(move || {
         //     if auth.is_logged_in() {
@@ -48,13 +113,77 @@ impl Sync<Stats> {
         // });
     }
 
-    // TODO: function to insert stats with version, returning null|version,
-    //       merge and re-push if necessary
-    pub async fn sync(&self) -> Result<()> {
-        // let mut user_stats = UserStats::new(self.auth, self.local.inner.read().clone());
-        // user_stats.push().await?;
+    /// Merge local stats with the remote ones, e.g. from another device, and push the result,
+    /// dequeuing local changes unless more were made meanwhile. Since stats are derived from their
+    /// event log, merging the logs doesn't lose anything either side recorded.
+    pub async fn sync(&mut self) -> Result<()> {
+        let local = self.local.peek().clone();
+        let mut merged = local.clone();
+        merged.merge(&UserStats::fetch(self.auth).await?.data);
+        UserStats::new(self.auth, merged.clone()).push().await?;
+        if *self.local.peek() == local {
+            if merged != local {
+                self.local.set(merged);
+            }
+            self.pending.set(false);
+        }
         Ok(())
     }
+
+    /// Have the server start a game, so that it can record it once it's finished. `None` if it
+    /// can't, e.g. when offline or signed out, in which case the game won't be recorded.
+    pub async fn start_game(&self, pack: PackIdentifier) -> Option<GameSession> {
+        #[cfg(feature = "fullstack")]
+        {
+            let access_token = self.auth.access_token().filter(|_| ONLINE())?;
+            crate::server::start_game(access_token, pack)
+                .await
+                .inspect_err(|e| tracing::warn!("Failed to start game: {e}"))
+                .ok()
+        }
+        #[cfg(not(feature = "fullstack"))]
+        {
+            let _ = pack;
+            None
+        }
+    }
+
+    /// Queue a finished game for the server to record.
+    pub fn record_game(&mut self, game: GameRecord) {
+        if cfg!(feature = "fullstack") && self.auth.is_logged_in() {
+            self.games.write().push(game);
+        }
+    }
+
+    /// Send queued games to the server, dequeuing those it has recorded or rejected. Stops at the
+    /// first request that fails, leaving the rest queued to try again later.
+    #[cfg(feature = "fullstack")]
+    async fn record_games(&mut self) -> std::result::Result<(), ServerFnError> {
+        let Some(access_token) = self.auth.access_token() else {
+            return Ok(());
+        };
+        self.recording.set(true);
+        let games = self.games.peek().clone();
+        let mut done = 0;
+        let mut result = Ok(());
+        for game in games {
+            match crate::server::record_game(access_token.clone(), game).await {
+                Ok(stats) => tracing::debug!("Recorded game, verified XP is {}", stats.xp()),
+                Err(e @ ServerFnError::Request(_)) => {
+                    result = Err(e);
+                    break;
+                }
+                // it won't be accepted next time either
+                Err(e) => tracing::warn!("Server rejected game: {e}"),
+            }
+            done += 1;
+        }
+        self.recording.set(false);
+        if done > 0 {
+            self.games.write().drain(..done);
+        }
+        result
+    }
 }
 
 impl Deref for Sync<Stats> {
@@ -73,19 +202,12 @@ impl DerefMut for Sync<Stats> {
 
 /// A type to match the public.stats table in the database.
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
-struct UserStats {
-    user_id: String, // TODO: uuid
-    data: Stats,
-    updated_at: DateTime<Utc>,
+pub struct UserStats {
+    pub user_id: String, // TODO: uuid
+    pub data: Stats,
+    pub updated_at: DateTime<Utc>,
 }
 
-impl SupabaseResource for UserStats {
-    fn table_name() -> &'static str {
-        "stats"
-    }
-}
-
-// TODO: these requests require overriding the anon jwt with the user's!
 impl UserStats {
     pub fn new(auth: AuthState, data: Stats) -> Self {
         Self {
@@ -98,14 +220,10 @@ impl UserStats {
     pub async fn fetch(auth: AuthState) -> Result<Self> {
         match auth.user_id() {
             None => Ok(UserStats::default()),
-            Some(user_id) => {
-                let mut stats = Self::request()
-                    .select("*")
-                    .eq("user_id", user_id)
-                    .execute()
-                    .await?;
-                Ok(stats.pop().unwrap_or_default())
-            }
+            Some(user_id) => Ok(datasource::source()
+                .load_stats(user_id)
+                .await?
+                .unwrap_or_default()),
         }
     }
 
@@ -114,14 +232,11 @@ impl UserStats {
         self
     }
 
-    // TODO: throw error on non-200
     pub async fn push(&mut self) -> Result<()> {
         tracing::debug!("Pushing stats for user_id {:?}", self.user_id);
         self.updated_at = Utc::now();
         if !self.user_id.is_empty() {
-            SupabaseRequest::<()>::rpc("upsert_stats", self)?
-                .execute()
-                .await?;
+            datasource::source().save_stats(self.clone()).await?;
         }
         Ok(())
     }
@@ -2,6 +2,7 @@ use dioxus::prelude::*;
 
 use crate::{
     supabase::MagicLinkResponse,
+    t,
     ui::{components::Modal, AppCtx, Route},
 };
 
@@ -44,18 +45,18 @@ pub fn Login() -> Element {
                 class: "w-64 px-3 py-2 rounded-lg border-none",
                 "type": "email",
                 name: "email",
-                placeholder: "Enter your email",
+                placeholder: t!("enter-email"),
                 oninput: move |event| email.set(event.value())
             }
             button {
                 class: "px-4 py-2 focus:outline-none focus-visible:ring focus-visible:ring-green-extra-dark font-semibold text-base bg-green-dark text-white rounded-full shadow",
                 disabled: magic_link_sent(),
-                "Login / Signup"
+                {t!("login-signup")}
             }
             if magic_link_sent() {
                 div {
                     class: "text-sm",
-                    "We sent a magic link to your email! Check your inbox and click the link to login."
+                    {t!("magic-link-sent")}
                 }
             }
         }
@@ -91,9 +92,9 @@ pub fn LoginRedirect(fragment: ReadOnlySignal<MagicLinkResponse>) -> Element {
             div {
                 class: "text-sm text-green",
                 if finished() {
-                    "Login successful!"
+                    {t!("login-successful")}
                 } else {
-                    "Logging in..."
+                    {t!("logging-in")}
                 }
             }
         }
@@ -1,6 +1,10 @@
 use dioxus::prelude::*;
 
-use crate::bird::Bird;
+use crate::{
+    bird::{Attribution, Bird, ImageSize},
+    i18n::LOCALE,
+    t,
+};
 
 #[derive(PartialEq, Props, Clone)]
 pub struct BirdCardProps {
@@ -52,14 +56,15 @@ pub fn BirdCard(props: BirdCardProps) -> Element {
                 },
                 img {
                     class: "border-2 w-24 h-24 rounded-full object-cover flex-none overflow-hidden",
-                    src: bird.image_url(),
+                    src: bird.image_url_sized(ImageSize::Small),
                     alt: "",
                 }
                 div {
                     class: "text-lg text-center mx-auto {select_class}",
-                    "{bird.common_name}"
+                    "{bird.common_name_for(LOCALE.read().code())}"
                 }
                 {children}
+                MediaAttribution { bird: bird.clone() }
             }
 
             div {
@@ -75,6 +80,50 @@ pub fn BirdCard(props: BirdCardProps) -> Element {
     }
 }
 
+/// A small line crediting the authors of a bird's image and default sound.
+#[component]
+pub fn MediaAttribution(bird: Bird, extra_classes: Option<String>) -> Element {
+    let extra_classes = extra_classes.unwrap_or_default();
+    let image = bird.image_attribution().cloned();
+    let sound = bird.default_sound_attribution().cloned();
+    if image.is_none() && sound.is_none() {
+        return rsx! {};
+    }
+    rsx! {
+        div {
+            class: "text-[0.6rem] leading-tight text-center opacity-75 normal-case {extra_classes}",
+            if let Some(attribution) = image {
+                AttributionLine { label: t!("photo-credit"), attribution }
+            }
+            if let Some(attribution) = sound {
+                AttributionLine { label: t!("audio-credit"), attribution }
+            }
+        }
+    }
+}
+
+#[component]
+fn AttributionLine(label: String, attribution: Attribution) -> Element {
+    let text = attribution.to_string();
+    rsx! {
+        div {
+            span { "{label}: " }
+            if let Some(url) = attribution.source_url.clone() {
+                a {
+                    class: "underline",
+                    href: url,
+                    target: "_blank",
+                    rel: "noopener noreferrer",
+                    onclick: |e| e.stop_propagation(),
+                    if text.is_empty() { {t!("source")} } else { "{text}" }
+                }
+            } else {
+                span { "{text}" }
+            }
+        }
+    }
+}
+
 #[derive(PartialEq, Props, Clone)]
 pub struct BirdCardPlaceholderProps {
     extra_classes: Option<String>,
@@ -1,44 +1,49 @@
 use dioxus::prelude::*;
 
-use super::{bird::BirdCard, icons::ArrowUturnRightIcon};
+use super::{bird::BirdCard, icons::ArrowUturnRightIcon, retry::RetryButton};
 use crate::{
     bird::{Bird, BirdPack},
+    i18n,
     pack::Pack,
+    t,
     ui::{pages::PLAY_STATUS, Route},
 };
 
 /// Pack of the day
 #[component]
 pub fn PackOfTheDay() -> Element {
-    let pack = use_resource(BirdPack::fetch_today);
+    let mut pack = use_resource(BirdPack::fetch_today);
     match &*pack.read_unchecked() {
         None => rsx! { PackOfTheDayPlaceholder {} },
         Some(Ok(pack)) => rsx! { PackOfTheDayInner {pack: pack.clone()} },
-        Some(Err(e)) => rsx! {
-            div {
-                class: "text-red-dark text-center flex flex-col items-center justify-center gap-6 mb-auto",
-                div { class: "text-3xl", "Uh oh! 😱" }
+        Some(Err(e)) => {
+            let report = t!("report-error", link = i18n::ELEMENT);
+            let (before, after) = i18n::split_at_element(&report);
+            rsx! {
                 div {
-                    class: "text-lg",
-                    span {
-                        "Something went wrong fetching today's challenge. Please open a "
-                    }
-                    a {
-                        class: "underline text-purple-dark",
-                        href: "https://github.com/samtay/birdtalk/issues/new",
-                        target: "_blank",
-                        "GitHub issue"
+                    class: "text-red-dark text-center flex flex-col items-center justify-center gap-6 mb-auto",
+                    div { class: "text-3xl", {t!("uh-oh")} }
+                    div {
+                        class: "text-lg",
+                        span { {t!("error-fetching-daily-pack")} " " "{before}" }
+                        a {
+                            class: "underline text-purple-dark",
+                            href: "https://github.com/samtay/birdtalk/issues/new",
+                            target: "_blank",
+                            {t!("github-issue")}
+                        }
+                        span { "{after}" }
                     }
-                    span { " with the following error:" }
-                }
-                div {
-                    code {
-                        class: "select-all",
-                        "{e}"
+                    div {
+                        code {
+                            class: "select-all",
+                            "{e.localized()}"
+                        }
                     }
+                    RetryButton { onretry: move |_| pack.restart() }
                 }
             }
-        },
+        }
     }
 }
 
@@ -60,7 +65,7 @@ fn PackOfTheDayInner(pack: BirdPack) -> Element {
                     position.with_mut(|p| *p = (*p + 1) % pack_size);
                 },
                 ArrowUturnRightIcon {}
-                span { class: "sr-only", "Next Bird" }
+                span { class: "sr-only", {t!("next-bird")} }
             }
             div {
                 class: "col-start-2 col-span-3 justify-self-stretch flex flex-col gap-6 items-center justify-center",
@@ -78,7 +83,7 @@ fn PackOfTheDayInner(pack: BirdPack) -> Element {
                         *PLAY_STATUS.write() = Some(pack);
                         navigator().push(Route::Play {pack_id});
                     },
-                    "play"
+                    {t!("play")}
                 }
             }
         }
@@ -214,10 +219,10 @@ fn Audio(url: String, user_playing: Signal<bool>, visible: ReadOnlySignal<bool>)
             },
             if this_playing() {
                 PauseIcon {}
-                span { class: "sr-only", "Pause" }
+                span { class: "sr-only", {t!("pause")} }
             } else {
                 SoundIcon {}
-                span { class: "sr-only", "Play" }
+                span { class: "sr-only", {t!("play-audio")} }
             }
         }
         audio {
@@ -237,7 +242,7 @@ fn Audio(url: String, user_playing: Signal<bool>, visible: ReadOnlySignal<bool>)
                 r#type: "audio/mpeg",
                 src: url
             }
-            "Your browser does not support the audio element."
+            {t!("audio-not-supported")}
         }
     }
 }
@@ -0,0 +1,90 @@
+//! Charts drawn with SVG, so that they don't need a charting library or service.
+
+use dioxus::prelude::*;
+
+/// Size of the charts' view box, which is stretched to fit them.
+const WIDTH: f64 = 300.0;
+const HEIGHT: f64 = 100.0;
+
+/// A line through `values`, from zero at the bottom up to `max` at the top, with labels under
+/// either end.
+#[component]
+pub fn LineChart(
+    title: String,
+    values: Vec<f64>,
+    max: f64,
+    start_label: String,
+    end_label: String,
+) -> Element {
+    let points = polyline_points(&values, max);
+    rsx! {
+        figure {
+            class: "flex flex-col gap-2",
+            figcaption { class: "text-xl", "{title}" }
+            svg {
+                class: "w-full h-32 text-green-dark overflow-visible",
+                view_box: "0 0 {WIDTH} {HEIGHT}",
+                "preserveAspectRatio": "none",
+                xmlns: "http://www.w3.org/2000/svg",
+                "aria-hidden": "true",
+                line {
+                    x1: "0",
+                    y1: "{HEIGHT}",
+                    x2: "{WIDTH}",
+                    y2: "{HEIGHT}",
+                    stroke: "currentColor",
+                    stroke_opacity: "0.25",
+                    "vector-effect": "non-scaling-stroke",
+                }
+                polyline {
+                    points,
+                    fill: "none",
+                    stroke: "currentColor",
+                    stroke_width: "2",
+                    stroke_linejoin: "round",
+                    "vector-effect": "non-scaling-stroke",
+                }
+            }
+            div {
+                class: "flex flex-row justify-between text-sm",
+                span { "{start_label}" }
+                span { "{end_label}" }
+            }
+        }
+    }
+}
+
+/// The points of a line through `values`, spread evenly across the chart. A single value is drawn
+/// as a flat line.
+fn polyline_points(values: &[f64], max: f64) -> String {
+    let y = |value: f64| HEIGHT - (value / max.max(f64::EPSILON)).clamp(0.0, 1.0) * HEIGHT;
+    match values {
+        [] => String::new(),
+        [value] => format!("0,{y} {WIDTH},{y}", y = y(*value)),
+        _ => {
+            let step = WIDTH / (values.len() - 1) as f64;
+            values
+                .iter()
+                .enumerate()
+                .map(|(ix, value)| format!("{},{}", ix as f64 * step, y(*value)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spreads_points_across_the_chart() {
+        assert_eq!(polyline_points(&[], 1.0), "");
+        assert_eq!(polyline_points(&[5.0], 10.0), "0,50 300,50");
+        assert_eq!(
+            polyline_points(&[0.0, 5.0, 20.0], 10.0),
+            "0,100 150,50 300,0"
+        );
+        assert_eq!(polyline_points(&[0.0, 0.0], 0.0), "0,100 300,100");
+    }
+}
@@ -1,6 +1,6 @@
 use dioxus::prelude::*;
 
-use crate::ui::Route;
+use crate::{t, ui::Route};
 
 #[component]
 pub fn Header() -> Element {
@@ -46,7 +46,7 @@ fn AviaryLink() -> Element {
                 id: "aviary-header-img",
                 class: "h-10 w-8 sm:h-12 sm:w-10",
             }
-            span { class: "sr-only", "Your Aviary" }
+            span { class: "sr-only", {t!("your-aviary")} }
         }
         style {
             dangerous_inner_html: r#"
@@ -83,7 +83,7 @@ fn DonateLink() -> Element {
                 id: "donate-header-img",
                 class: "h-10 w-8 sm:h-12 sm:w-10",
             }
-            span { class: "sr-only", "Donate" }
+            span { class: "sr-only", {t!("donate")} }
         }
         style {
             dangerous_inner_html: r#"
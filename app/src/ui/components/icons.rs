@@ -177,6 +177,26 @@ pub fn UncheckedCircle(extra_classes: Option<String>) -> Element {
     }
 }
 
+#[component]
+pub fn LockIcon(extra_classes: Option<String>) -> Element {
+    let extra_classes = extra_classes.unwrap_or_default();
+    rsx! {
+        svg {
+            class: "w-6 h-6 {extra_classes}",
+            fill: "none",
+            stroke: "currentColor",
+            stroke_width: "1.5",
+            view_box: "0 0 24 24",
+            xmlns: "http://www.w3.org/2000/svg",
+            path {
+                d: "M16.5 10.5V6.75a4.5 4.5 0 1 0-9 0v3.75m-.75 11.25h10.5a2.25 2.25 0 0 0 2.25-2.25v-6.75a2.25 2.25 0 0 0-2.25-2.25H6.75a2.25 2.25 0 0 0-2.25 2.25v6.75a2.25 2.25 0 0 0 2.25 2.25Z",
+                stroke_linecap: "round",
+                stroke_linejoin: "round"
+            }
+        }
+    }
+}
+
 #[component]
 pub fn Github(extra_classes: Option<String>) -> Element {
     let extra_classes = extra_classes.unwrap_or_default();
@@ -0,0 +1,30 @@
+use dioxus::prelude::*;
+
+use crate::{
+    i18n::{Locale, LOCALE},
+    t,
+    ui::AppCtx,
+};
+
+/// A dropdown to override the language detected from the browser.
+#[component]
+pub fn LocaleSelect() -> Element {
+    let mut locale_setting = use_context::<AppCtx>().locale_setting;
+    rsx! {
+        label {
+            class: "ml-2 rounded has-[:focus-visible]:ring",
+            span { class: "sr-only", {t!("language")} }
+            select {
+                class: "bg-transparent text-sm cursor-pointer outline-none",
+                onchange: move |e| locale_setting.set(Locale::from_tag(&e.value())),
+                for locale in Locale::ALL {
+                    option {
+                        value: locale.code(),
+                        selected: *LOCALE.read() == locale,
+                        "{locale.name()}"
+                    }
+                }
+            }
+        }
+    }
+}
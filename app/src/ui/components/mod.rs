@@ -3,12 +3,20 @@
 mod auth;
 mod bird;
 mod birdpack;
+mod chart;
 mod header;
 pub mod icons;
+mod locale;
 mod modal;
+mod retry;
+mod upgrade;
 
 pub use auth::*;
 pub use bird::*;
 pub use birdpack::*;
+pub use chart::*;
 pub use header::*;
+pub use locale::*;
 pub use modal::*;
+pub use retry::*;
+pub use upgrade::*;
@@ -1,5 +1,7 @@
 use dioxus::prelude::*;
 
+use crate::t;
+
 #[derive(PartialEq, Clone, Props)]
 pub struct ModalProps {
     pub children: Element,
@@ -91,7 +93,7 @@ pub fn Modal(props: ModalProps) -> Element {
                             stroke_linecap: "round"
                         }
                     }
-                    span { class: "sr-only", "Close Modal" }
+                    span { class: "sr-only", {t!("close-modal")} }
 
                 }
                 {children}
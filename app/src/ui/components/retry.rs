@@ -0,0 +1,15 @@
+use dioxus::prelude::*;
+
+use crate::t;
+
+/// A button to try a failed request again, for error views.
+#[component]
+pub fn RetryButton(onretry: EventHandler) -> Element {
+    rsx! {
+        button {
+            class: "px-6 py-2 border-2 border-red-dark focus:outline-none focus-visible:ring focus-visible:ring-red-dark font-semibold text-red-dark bg-offwhite rounded-xl shadow sm:hover:shadow-xl sm:hover:scale-110 transition-transform",
+            onclick: move |_| onretry.call(()),
+            {t!("retry")}
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use dioxus::prelude::*;
+
+use super::{auth::Login, icons::LockIcon};
+use crate::{
+    payments::{self, Checkout, Customer, Product},
+    t,
+    ui::AppCtx,
+    utils,
+};
+
+/// Buy `product`, asking the user to sign in first if needed.
+#[component]
+pub fn UpgradeButton(product: Product) -> Element {
+    let ctx = use_context::<AppCtx>();
+    let mut entitlements = ctx.entitlements;
+    let mut pending = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+    let mut show_login = use_signal(|| false);
+
+    let label = match product {
+        Product::AllPacks => t!("unlock-all-packs"),
+        Product::Pack(_) => t!("unlock-pack"),
+    };
+    let checkout = move |_| async move {
+        let Some(user_id) = ctx.auth_state.user_id() else {
+            show_login.set(true);
+            return;
+        };
+        let customer = Customer {
+            user_id,
+            email: ctx.auth_state.email(),
+        };
+        pending.set(true);
+        error.set(None);
+        match payments::provider().checkout(customer, product).await {
+            Ok(Checkout::Redirect(url)) => utils::redirect(&url),
+            Ok(Checkout::Granted(entitlement)) => entitlements.write().grant(entitlement),
+            Err(e) => {
+                tracing::error!("Checkout failed: {e}");
+                error.set(Some(t!("error-checkout")));
+            }
+        }
+        pending.set(false);
+    };
+
+    rsx! {
+        div {
+            class: "flex flex-col items-center gap-4",
+            button {
+                class: "px-8 py-3 border-2 border-purple-dark focus:outline-none focus-visible:ring focus-visible:ring-purple-dark font-semibold bg-purple text-white rounded-xl shadow sm:hover:shadow-xl sm:hover:scale-110 transition-transform uppercase flex items-center gap-2",
+                disabled: pending(),
+                onclick: checkout,
+                LockIcon { extra_classes: "w-5 h-5" }
+                "{label}"
+            }
+            if let Some(error) = error() {
+                div { class: "text-red-dark text-sm", "{error}" }
+            }
+            if show_login() && !ctx.auth_state.is_logged_in() {
+                div {
+                    class: "text-center",
+                    div { class: "mb-2 text-sm", {t!("sign-in-to-unlock")} }
+                    Login {}
+                }
+            }
+        }
+    }
+}
+
+/// Shown in place of a pack the user hasn't paid for.
+#[component]
+pub fn LockedPack() -> Element {
+    rsx! {
+        div {
+            class: "text-center flex flex-col items-center justify-center gap-6 mb-auto mt-4 px-4",
+            LockIcon { extra_classes: "w-12 h-12 text-purple-dark" }
+            div { class: "text-3xl", {t!("pack-locked-title")} }
+            div { class: "text-lg max-w-md", {t!("pack-locked")} }
+            UpgradeButton { product: Product::AllPacks }
+        }
+    }
+}
@@ -1,12 +1,13 @@
 use dioxus::prelude::*;
 
-use crate::bird::Bird;
+use crate::{bird::Bird, t};
 
 const AUDIO_LOOP: bool = true;
 const AUDIO_AUTOPLAY: bool = true;
 
+/// Plays the bird's recording, calling `onplay` whenever it starts or resumes playing.
 #[component]
-pub fn AudioPlayer(bird: Memo<Bird>) -> Element {
+pub fn AudioPlayer(bird: Memo<Bird>, onplay: EventHandler) -> Element {
     use wasm_bindgen::JsCast;
     use web_sys::HtmlAudioElement;
 
@@ -59,7 +60,7 @@ pub fn AudioPlayer(bird: Memo<Bird>) -> Element {
             }
             span {
                 class: "sr-only",
-                if playing() { "Pause" } else { "Play" }
+                if playing() { {t!("pause")} } else { {t!("play-audio")} }
             }
         }
         audio {
@@ -69,7 +70,10 @@ pub fn AudioPlayer(bird: Memo<Bird>) -> Element {
                         mnt.downcast::<web_sys::Element>().cloned().map(|el| el.unchecked_into()),
                     )
             },
-            onplay: move |_| *playing.write() = true,
+            onplay: move |_| {
+                *playing.write() = true;
+                onplay.call(());
+            },
             onpause: move |_| *playing.write() = false,
             // controls: "true",
             preload: "auto",
@@ -79,7 +83,7 @@ pub fn AudioPlayer(bird: Memo<Bird>) -> Element {
                 r#type: "audio/mpeg",
                 src: bird.read().default_sound_url()
             }
-            "Your browser does not support the audio element."
+            {t!("audio-not-supported")}
         }
     }
 }
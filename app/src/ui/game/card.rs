@@ -3,7 +3,13 @@ use std::rc::Rc;
 use dioxus::prelude::*;
 
 use super::{quiz::BirdContext, GameCtx};
-use crate::{bird::Bird, stats::LEARN_THRESHOLD};
+use crate::{
+    bird::{Bird, ImageSize},
+    i18n::LOCALE,
+    stats::LEARN_THRESHOLD,
+    t,
+    ui::components::MediaAttribution,
+};
 
 #[component]
 pub fn MultipleChoiceCard(bird: MappedSignal<BirdContext>, correct: bool) -> Element {
@@ -52,7 +58,7 @@ fn CardFront(bird: Memo<Bird>, correct: bool) -> Element {
                 if !correct {
                     mistakenly_chosen.set(true);
                 }
-                game_ctx.record_choice(correct);
+                game_ctx.record_choice(bird.peek().id);
             },
             class: "group w-full sm:max-md:max-w-xs h-full mx-auto border border-black rounded-xl shadow sm:enabled:hover:shadow-lg sm:enabled:hover:bg-yellow sm:enabled:hover:-translate-y-2 transition-transform bg-yellow-light focus:outline-none focus-visible:ring focus-visible:ring-yellow-dark disabled:shadow-none disabled:border disabled:opacity-50 disabled:transition-opacity disabled:duration-1000",
             class: "p-2 sm:p-4 flex flex-row sm:flex-col space-between items-center gap-1 sm:gap-4",
@@ -60,7 +66,7 @@ fn CardFront(bird: Memo<Bird>, correct: bool) -> Element {
             disabled: mistakenly_chosen() || correct_chosen(),
             img {
                 class: "border block w-20 h-20 sm:w-28 sm:h-28 rounded-full object-cover",
-                src: bird.read().image_url(),
+                src: bird.read().image_url_sized(ImageSize::Medium),
                 alt: "",
             }
             div {
@@ -68,7 +74,7 @@ fn CardFront(bird: Memo<Bird>, correct: bool) -> Element {
                 span {
                     class: "text-base sm:text-lg font-semibold",
                     // "American Three-toed Woodpecker"
-                    "{bird().common_name}"
+                    "{bird().common_name_for(LOCALE.read().code())}"
                 }
                 span {
                     class: "text-sm font-medium",
@@ -100,7 +106,7 @@ fn CardBack(bird: MappedSignal<BirdContext>, correct: bool) -> Element {
             class: "p-2 sm:p-4 flex flex-row sm:flex-col space-between items-center gap-1",
             img {
                 class: "border block w-20 h-20 sm:w-28 sm:h-28 rounded-full object-cover",
-                src: bird.read().bird.image_url(),
+                src: bird.read().bird.image_url_sized(ImageSize::Medium),
                 alt: "",
             }
             div {
@@ -108,9 +114,10 @@ fn CardBack(bird: MappedSignal<BirdContext>, correct: bool) -> Element {
                 div {
                     class: "text-base sm:text-lg font-semibold",
                     // "American Three-toed Woodpecker"
-                    "{bird().bird.common_name}"
+                    "{bird().bird.common_name_for(LOCALE.read().code())}"
                 }
                 BirdProgress { bird: bird.clone() }
+                MediaAttribution { bird: bird.read().bird.clone() }
                 button {
                     class: "mt-2 px-2 py-1 focus:outline-none focus-visible:ring-2 focus-visible:ring-green-extra-dark font-semibold text-sm sm:text-base bg-green-dark text-white rounded-xl shadow sm:hover:scale-[1.05] sm:hover:shadow-xl transition-transform",
                     onclick: move |_| async move {
@@ -124,7 +131,7 @@ fn CardBack(bird: MappedSignal<BirdContext>, correct: bool) -> Element {
                         next_button.set(Some(e.data()));
                     },
                     disabled: !next_button_enabled(),
-                    "Continue"
+                    {t!("continue")}
                 }
             }
         }
@@ -2,8 +2,9 @@ use dioxus::prelude::*;
 
 use crate::{
     stats::Stats,
+    t,
     ui::{
-        components::{Login, Modal},
+        components::{icons::TrophyIcon, Login, Modal},
         game::GameCtx,
         pages::PLAY_STATUS,
         AppCtx, Route,
@@ -25,6 +26,10 @@ pub fn GameOverModal() -> Element {
     });
 
     let _auth = use_context::<AppCtx>().auth_state;
+    let achievements_unlocked = use_context::<GameCtx>()
+        .achievements_unlocked
+        .read()
+        .clone();
 
     rsx! {
         Modal {
@@ -33,13 +38,32 @@ pub fn GameOverModal() -> Element {
                 class: "p-2 sm:p-4 mx-auto my-2 flex flex-col items-center gap-4 sm:gap-8 text-center",
                 h1 {
                     class: "text-3xl font-bold bg-clip-text text-transparent bg-gradient-to-r from-green to-green-extra-dark uppercase",
-                    "Nice work!"
+                    {t!("nice-work")}
                 }
                 table { class: "table-auto text-lg",
                     tbody {
-                        Stat { name: "XP", f: Stats::xp }
-                        Stat { name: "Birds Learned", f: Stats::total_birds_learned }
-                        Stat { name: "Daily Pack Streak", f: Stats::active_daily_pack_streak, fprev: Some(Stats::latest_daily_pack_streak)}
+                        Stat { name: "stat-xp", f: Stats::xp }
+                        Stat { name: "stat-birds-learned", f: Stats::total_birds_learned }
+                        Stat { name: "stat-daily-pack-streak", f: Stats::active_daily_pack_streak, fprev: Some(Stats::latest_daily_pack_streak)}
+                    }
+                }
+                if !achievements_unlocked.is_empty() {
+                    div {
+                        class: "flex flex-col items-center gap-2",
+                        h3 {
+                            class: "text-xl font-bold flex flex-row items-center gap-1",
+                            TrophyIcon {}
+                            {t!("achievements-unlocked", count = achievements_unlocked.len())}
+                        }
+                        ul {
+                            for achievement in achievements_unlocked {
+                                li {
+                                    key: "{achievement.id}",
+                                    span { class: "font-semibold", "{achievement.name()}" }
+                                    ": {achievement.description()}"
+                                }
+                            }
+                        }
                     }
                 }
                 // TODO: uncomment when auth is fully implemented
@@ -58,13 +82,13 @@ pub fn GameOverModal() -> Element {
                             async_std::task::sleep(std::time::Duration::from_millis(500)).await;
                             mnt.set_focus(true).await.ok();
                         },
-                        "Continue"
+                        {t!("continue")}
                     }
                 } else {
                     div {
                         h3 {
                             class: "mb-2 text-xl font-bold bg-clip-text text-transparent bg-gradient-to-r from-green to-green-extra-dark",
-                            "Sign up to save your progress!"
+                            {t!("sign-up-to-save")}
                         }
                         Login {}
                     }
@@ -76,6 +100,7 @@ pub fn GameOverModal() -> Element {
 
 #[derive(PartialEq, Clone, Props)]
 struct StatProps {
+    /// Message id of the stat's name
     name: &'static str,
     f: fn(&Stats) -> u32,
     /// Optionally specify a different function to get the previous stats value.
@@ -97,7 +122,7 @@ fn Stat(StatProps { name, f, fprev }: StatProps) -> Element {
     let change = value.checked_sub(og_value).filter(|v| *v > 0);
     rsx! {
         tr {
-            td { class: "text-right px-1", "{name}:" }
+            td { class: "text-right px-1", {t!(name)} ":" }
             td { class: "text-left px-1", "{value}"  }
             if let Some(change) = change {
                 td { class: "text-left px-2 text-green-dark font-bold", "+{change}"}
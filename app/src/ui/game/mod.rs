@@ -5,10 +5,19 @@ mod card;
 mod game_over;
 pub mod quiz;
 
+use chrono::{DateTime, Utc};
 use dioxus::prelude::*;
-use rand::prelude::SliceRandom;
 
-use crate::{bird::Bird, pack::Pack, stats::Stats, ui::AppCtx};
+use crate::{
+    achievements::{Achievement, FamilyBirds},
+    bird::Bird,
+    clock::Clock,
+    pack::Pack,
+    server::{GameRecord, GameSession},
+    stats::{Answer, Mode, Stats},
+    sync::Sync,
+    ui::AppCtx,
+};
 use audio::AudioPlayer;
 use card::{MultipleChoiceCard, MultipleChoiceCardPlaceholder};
 use game_over::GameOverModal;
@@ -20,10 +29,25 @@ struct GameCtx {
     game: Signal<Game>,
     /// Pack
     pack: CopyValue<Pack>,
+    /// Whether the pack is a past daily pack, played from the archive
+    from_archive: bool,
     /// Storage backed stats state
-    stats: Signal<Stats>,
+    stats: Sync<Stats>,
+    /// What time it is, which is the stats' clock
+    clock: Clock,
+    /// The game as started by the server, if it was
+    session: CopyValue<Option<GameSession>>,
+    /// When the game started, which identifies its answers in the stats log
+    started_at: DateTime<Utc>,
+    /// When the recording for the current challenge started playing, or when the challenge was
+    /// shown until it does, which is what answers are timed from
+    challenge_started_at: CopyValue<DateTime<Utc>>,
+    /// Whether the recording for the current challenge has started playing
+    audio_started: CopyValue<bool>,
     /// Value of `stats` at the game start (so we can diff at the end).
     stats_original: CopyValue<Stats>,
+    /// Achievements unlocked by completing the game
+    achievements_unlocked: CopyValue<Vec<&'static Achievement>>,
     /// Has a correct choice been made for this multiple choice yet?
     correct_chosen: Signal<bool>,
     /// Signal that the game has been completed
@@ -35,23 +59,42 @@ struct GameCtx {
 }
 
 impl GameCtx {
-    /// Initialize a new game context (and provide it to children).
-    fn init(pack: Pack) -> Self {
+    /// Initialize a new game context (and provide it to children), with a game shuffled by `seed`.
+    fn init(pack: Pack, from_archive: bool, seed: u64) -> Self {
         let app_ctx = use_context::<AppCtx>();
-        let game = use_signal(|| Game::init(pack.birds.clone(), true));
+        let game = use_signal(|| Game::init(pack.birds.clone(), seed));
+        let stats = app_ctx.stats;
+        let clock = stats.peek().clock();
+        let mut session = use_hook(|| CopyValue::new(None));
+        let pack_id = pack.id.clone();
+        use_hook(move || {
+            spawn(async move {
+                session.set(stats.start_game(pack_id).await);
+            })
+        });
+        let started_at = use_hook(|| clock.now());
+        let challenge_started_at = use_hook(|| CopyValue::new(started_at));
+        let audio_started = use_hook(|| CopyValue::new(false));
         let pack = use_hook(|| CopyValue::new(pack));
-        let stats = *app_ctx.stats;
         let stats_original_peek = stats.peek();
         let stats_original = use_hook(|| CopyValue::new(stats_original_peek.clone()));
+        let achievements_unlocked = use_hook(|| CopyValue::new(Vec::new()));
         let correct_chosen = use_signal(|| false);
         let game_completed = use_signal(|| false);
         use_context_provider(|| Self {
             game,
             stats,
+            clock,
+            session,
+            started_at,
+            challenge_started_at,
+            audio_started,
             correct_chosen,
             pack,
+            from_archive,
             game_completed,
             stats_original,
+            achievements_unlocked,
         })
     }
 
@@ -67,17 +110,11 @@ impl GameCtx {
         use_memo(move || game.read().correct_choice().bird.clone())
     }
 
-    /// Create a shuffle of 0..4 that will shuffle itself on changes to birds
-    // Can maybe subscribe to a "turn" so shuffle only runs per turn
-    // For now just hack this to change when the actual birds change
+    /// Create a new memo signal of the order to show the current choices in, which is shuffled
+    /// for every challenge
     fn shuffle_memo(&self) -> Memo<Vec<usize>> {
-        let birds = self.birds_memo();
-        use_memo(move || {
-            let _ = birds.read(); // subscribe to birds
-            let mut indices = (0..MULTIPLE_CHOICE_SIZE).collect::<Vec<_>>();
-            indices.shuffle(&mut rand::thread_rng());
-            indices
-        })
+        let game = self.game;
+        use_memo(move || game.read().choice_order().to_vec())
     }
 
     fn progress(&self) -> Memo<(usize, usize)> {
@@ -85,24 +122,68 @@ impl GameCtx {
         use_memo(move || game.read().progress())
     }
 
-    fn record_choice(&mut self, correct: bool) {
-        self.game.write().record_choice(correct);
-        let game = self.game.read();
-        let choice = game.correct_choice();
-        let mut stats = self.stats.write();
-        if correct {
-            stats.add_correct_id(choice.learned(), choice.bird.id);
-        } else {
-            stats.add_incorrect_id(choice.bird.id);
-        }
+    /// Record that the bird with id `chosen` was picked for the current challenge.
+    fn record_choice(&mut self, chosen: u64) {
+        let at = self.clock.now();
+        let bird_id = self.game.read().correct_choice().bird.id;
+        let correct = chosen == bird_id;
+        let response_time = at - *self.challenge_started_at.read();
+        let response_time_ms = response_time.num_milliseconds().max(0) as u32;
+        self.game.write().record_choice(correct, response_time_ms);
+        self.stats.write().record_answer(Answer {
+            at,
+            game_started_at: self.started_at,
+            bird_id,
+            pack: self.pack.read().id.clone(),
+            mode: Mode::MultipleChoice,
+            response_time_ms,
+            chosen,
+        });
         self.correct_chosen.set(correct);
     }
 
+    /// Start timing the current challenge, the first time its recording plays.
+    fn audio_started(&mut self) {
+        if !*self.audio_started.read() {
+            self.audio_started.set(true);
+            self.challenge_started_at.set(self.clock.now());
+        }
+    }
+
     async fn next(&mut self) {
         if self.game.read().is_complete() {
-            self.stats.write().add_pack_completed(&self.pack.read());
+            let families = Achievement::families_in(&self.pack.read());
+            let families = match Bird::fetch_family_ids(families).await {
+                Ok(families) => families,
+                // their achievements can be unlocked by a later game
+                Err(e) => {
+                    tracing::warn!("Failed to fetch birds of the pack's families: {e}");
+                    FamilyBirds::new()
+                }
+            };
+            let now = self.clock.now();
+            let unlocked = {
+                let mut stats = self.stats.write();
+                // from the first game on, days are counted in the time zone it was played in,
+                // until the user picks another
+                if stats.time_zone().is_none() {
+                    if let Some(time_zone) = stats.clock().local_time_zone() {
+                        stats.pin_time_zone(time_zone);
+                    }
+                }
+                stats.record_pack_completed(&self.pack.read(), now, self.from_archive);
+                stats.unlock_achievements(&self.pack.read(), &families, now)
+            };
+            self.achievements_unlocked.set(unlocked);
+            if let Some(session) = self.session.read().clone() {
+                let answers = self.stats.read().game_answers(self.started_at);
+                self.stats.record_game(GameRecord {
+                    session,
+                    answers,
+                    from_archive: self.from_archive,
+                });
+            }
             self.game_completed.set(true);
-            // TODO: sync stats on game completed
         } else {
             // Cards flip back to face up
             self.correct_chosen.set(false);
@@ -113,6 +194,8 @@ impl GameCtx {
 
             // Continue with next challenge
             self.game.write().set_next_challenge();
+            self.challenge_started_at.set(self.clock.now());
+            self.audio_started.set(false);
             tracing::debug!(
                 "Set new challenge! new bird is: {:?}",
                 self.game.read().correct_choice().bird.common_name
@@ -122,8 +205,9 @@ impl GameCtx {
 }
 
 #[component]
-pub fn GameView(pack: Pack) -> Element {
-    let game_ctx = GameCtx::init(pack);
+pub fn GameView(pack: Pack, from_archive: bool) -> Element {
+    let seed = use_hook(rand::random);
+    let mut game_ctx = GameCtx::init(pack, from_archive, seed);
     let shuffle = game_ctx.shuffle_memo();
     let correct_bird = game_ctx.correct_bird_memo();
 
@@ -138,7 +222,8 @@ pub fn GameView(pack: Pack) -> Element {
                 div {
                     class: "",
                     AudioPlayer {
-                        bird: correct_bird
+                        bird: correct_bird,
+                        onplay: move |_| game_ctx.audio_started(),
                     }
                 }
                 div {
@@ -1,9 +1,12 @@
 use std::mem;
 
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 use serde::{Deserialize, Serialize};
 
-use crate::{bird::Bird, stats::LEARN_THRESHOLD};
+use crate::{
+    bird::Bird,
+    stats::{self, LEARN_THRESHOLD},
+};
 
 pub const MULTIPLE_CHOICE_SIZE: usize = 4;
 
@@ -18,24 +21,34 @@ pub struct Game {
     /// The rest of the birdpack.
     pack: Vec<BirdContext>,
 
+    /// The order the current choices are shown in, as indices into `choices`.
+    choice_order: Vec<usize>,
+
     /// Has this bird pack already been learned?
     already_learned: bool,
+
+    /// Where the game's randomness comes from, so that a game can be replayed from its seed.
+    #[serde(skip, default = "StdRng::from_entropy")]
+    rng: StdRng,
 }
 
 impl Game {
-    /// Initialize the game.
-    pub fn init(birds: Vec<Bird>, shuffle: bool) -> Self {
+    /// Initialize the game, shuffling `birds` with a random number generator seeded with `seed`.
+    pub fn init(birds: Vec<Bird>, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
         let mut choices: Vec<_> = birds.into_iter().map(BirdContext::from).collect();
-        if shuffle {
-            choices.shuffle(&mut thread_rng());
-        }
+        choices.shuffle(&mut rng);
         let pack = choices.split_off(MULTIPLE_CHOICE_SIZE);
 
-        Self {
+        let mut game = Self {
             choices,
             pack,
+            choice_order: (0..MULTIPLE_CHOICE_SIZE).collect(),
             already_learned: false,
-        }
+            rng,
+        };
+        game.shuffle_choice_order();
+        game
     }
 
     /// Get the current multiple choices.
@@ -48,6 +61,11 @@ impl Game {
         self.choices.clone().into_iter().map(|bc| bc.bird).collect()
     }
 
+    /// Get the order to show the current choices in, as indices into [`Game::choices`].
+    pub fn choice_order(&self) -> &[usize] {
+        &self.choice_order
+    }
+
     /// Get the correct choice.
     pub fn correct_choice(&self) -> &BirdContext {
         self.choices.first().unwrap()
@@ -58,12 +76,13 @@ impl Game {
         self.choices.first_mut().unwrap()
     }
 
-    /// Record a choice made on the current challenge.
-    pub fn record_choice(&mut self, correct: bool) {
+    /// Record a choice made on the current challenge, `response_time_ms` after it started.
+    pub fn record_choice(&mut self, correct: bool, response_time_ms: u32) {
         let choice = self.correct_choice_mut();
         if correct {
             choice.identified += 1;
-            choice.consecutively_identified += 1;
+            choice.consecutively_identified += stats::learn_credit(correct, response_time_ms);
+            choice.response_times_ms.push(response_time_ms);
         } else {
             choice.mistaken += 1;
             choice.consecutively_identified = 0;
@@ -91,7 +110,7 @@ impl Game {
         self.pack.append(&mut rest_of_pack);
 
         // Keep sorted for next time
-        self.pack.shuffle(&mut thread_rng());
+        self.pack.shuffle(&mut self.rng);
         self.pack.sort_by_key(|ctx| {
             // The weights might need some randomization too.
             let mut weight: i32 = 0;
@@ -102,6 +121,11 @@ impl Game {
             weight -= ctx.last_seen.map(|ls| ls.max(5) as i32).unwrap_or(5);
             weight
         });
+        self.shuffle_choice_order();
+    }
+
+    fn shuffle_choice_order(&mut self) {
+        self.choice_order.shuffle(&mut self.rng);
     }
 
     /// Get the count of learned birds out of total birds.
@@ -133,7 +157,8 @@ pub struct BirdContext {
     pub bird: Bird,
     /// The number of times the bird has been correctly identified.
     pub identified: u32,
-    /// The number of times the bird has been correctly identified consecutively.
+    /// The number of times the bird has been correctly identified consecutively, with fluent
+    /// identifications counting double (see [`stats::learn_credit`]).
     ///
     /// A bird is considered learned when this value reaches LEARN_THRESHOLD.
     pub consecutively_identified: u32,
@@ -141,6 +166,8 @@ pub struct BirdContext {
     pub mistaken: u32,
     /// The number of rounds since this bird was last seen.
     pub last_seen: Option<u32>,
+    /// How long each correct identification took, in milliseconds.
+    pub response_times_ms: Vec<u32>,
 }
 
 impl From<Bird> for BirdContext {
@@ -151,6 +178,7 @@ impl From<Bird> for BirdContext {
             consecutively_identified: 0,
             mistaken: 0,
             last_seen: None,
+            response_times_ms: Vec::new(),
         }
     }
 }
@@ -161,3 +189,145 @@ impl BirdContext {
         self.consecutively_identified >= LEARN_THRESHOLD
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, NaiveDate, Utc};
+    use chrono_tz::Tz;
+
+    use super::*;
+    use crate::{
+        clock::Clock,
+        pack::{Pack, PackIdentifier},
+        stats::{Answer, Mode, Stats},
+    };
+
+    fn birds(count: u64) -> Vec<Bird> {
+        (1..=count).map(Bird::fixture).collect()
+    }
+
+    /// Play `game` of `pack` to completion the way `GameCtx` does, answering every challenge
+    /// correctly after `response_time_ms` and recording it in `stats` as `clock` moves on. Returns
+    /// the birds asked, in order.
+    fn play(
+        game: &mut Game,
+        pack: &Pack,
+        stats: &mut Stats,
+        clock: &mut Clock,
+        response_time_ms: u32,
+    ) -> Vec<u64> {
+        let started_at = clock.now();
+        let mut asked = Vec::new();
+        loop {
+            clock.advance(Duration::milliseconds(response_time_ms.into()));
+            let bird_id = game.correct_choice().bird.id;
+            asked.push(bird_id);
+            game.record_choice(true, response_time_ms);
+            stats.record_answer(Answer {
+                at: clock.now(),
+                game_started_at: started_at,
+                bird_id,
+                pack: pack.id.clone(),
+                mode: Mode::MultipleChoice,
+                response_time_ms,
+                chosen: bird_id,
+            });
+            if game.is_complete() {
+                stats.record_pack_completed(pack, clock.now(), false);
+                return asked;
+            }
+            game.set_next_challenge();
+        }
+    }
+
+    fn at(at: &str) -> Clock {
+        Clock::Fixed(at.parse::<DateTime<Utc>>().unwrap())
+    }
+
+    #[test]
+    fn replays_games_from_their_seed() {
+        let pack = Pack::from(birds(8));
+        let mut played = Vec::new();
+        for _ in 0..2 {
+            let mut game = Game::init(pack.birds.clone(), 7);
+            let mut orders = vec![game.choice_order().to_vec()];
+            let mut clock = at("2024-10-01T12:00:00Z");
+            let asked = play(&mut game, &pack, &mut Stats::default(), &mut clock, 3000);
+            orders.push(game.choice_order().to_vec());
+            played.push((asked, orders));
+        }
+        assert_eq!(played[0], played[1]);
+    }
+
+    #[test]
+    fn shows_every_choice_once() {
+        let mut game = Game::init(birds(8), 1);
+        for _ in 0..10 {
+            let mut order = game.choice_order().to_vec();
+            order.sort();
+            assert_eq!(order, (0..MULTIPLE_CHOICE_SIZE).collect::<Vec<_>>());
+            game.set_next_challenge();
+        }
+    }
+
+    #[test]
+    fn completes_games_sooner_when_fluent() {
+        let pack = Pack::from(birds(8));
+        let mut clock = at("2024-10-01T12:00:00Z");
+        let mut stats = Stats::default();
+        let slow = play(
+            &mut Game::init(pack.birds.clone(), 3),
+            &pack,
+            &mut stats,
+            &mut clock,
+            3000,
+        );
+        assert_eq!(slow.len(), 8 * LEARN_THRESHOLD as usize);
+        assert_eq!(stats.total_birds_learned(), 8);
+
+        let mut game = Game::init(pack.birds.clone(), 3);
+        let fluent = play(&mut game, &pack, &mut stats, &mut clock, 1000);
+        assert_eq!(fluent.len(), 8 * 2);
+        assert_eq!(game.progress(), (8, 8));
+    }
+
+    #[test]
+    fn daily_pack_finished_after_midnight_counts_for_its_day() {
+        let day = |date: &str| date.parse::<NaiveDate>().unwrap();
+        let daily_pack = |date: &str| Pack {
+            id: PackIdentifier::Date(day(date)),
+            ..Pack::from(birds(8))
+        };
+        let mut clock = at("2024-10-01T23:59:50Z");
+        let mut stats = Stats::default();
+        stats.pin_time_zone(Tz::UTC);
+
+        let pack = daily_pack("2024-10-01");
+        play(
+            &mut Game::init(pack.birds.clone(), 5),
+            &pack,
+            &mut stats,
+            &mut clock,
+            3000,
+        );
+        assert!(clock.now() > "2024-10-02T00:00:00Z".parse::<DateTime<Utc>>().unwrap());
+        assert_eq!(stats.daily_packs_completed(), [day("2024-10-01")]);
+
+        // and the next day's pack still continues the streak
+        clock.advance(Duration::hours(12));
+        let pack = daily_pack("2024-10-02");
+        play(
+            &mut Game::init(pack.birds.clone(), 5),
+            &pack,
+            &mut stats,
+            &mut clock,
+            3000,
+        );
+        let stats = stats.with_clock(clock);
+        assert_eq!(stats.active_daily_pack_streak(), 2);
+
+        // but it's lost once a whole day is missed
+        clock.advance(Duration::days(2));
+        assert_eq!(stats.with_clock(clock).active_daily_pack_streak(), 0);
+    }
+}
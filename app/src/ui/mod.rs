@@ -2,33 +2,80 @@
 //! lives here.
 
 mod components;
-mod game;
+pub mod game;
 mod pages;
 
+use chrono::NaiveDate;
 use dioxus::prelude::*;
+use dioxus_sdk::storage::{use_synced_storage, LocalStorage};
 
 use crate::{
+    conf,
+    i18n::{self, Locale},
+    offline,
     pack::PackIdentifier,
+    payments::Entitlements,
     stats::Stats,
     supabase::AuthState,
     sync::Sync,
+    t,
     ui::{
-        components::{icons, Header},
-        pages::{Birds, Index, Play},
+        components::{icons, Header, LocaleSelect},
+        pages::{Archive, Birds, Index, Packs, Play, PlayArchived, Progress, Trophies},
     },
+    utils,
 };
 
 #[derive(Clone, Copy)]
 pub struct AppCtx {
     pub auth_state: AuthState,
     pub stats: Sync<Stats>,
+    /// The user's chosen locale, if they've overridden their browser's language
+    pub locale_setting: Signal<Option<Locale>>,
+    /// What the signed in user has paid for
+    pub entitlements: Signal<Entitlements>,
 }
 
 impl AppCtx {
     pub fn init() {
         let auth_state = AuthState::init();
         let stats = Sync::<Stats>::init(auth_state);
-        use_context_provider(|| Self { auth_state, stats });
+        let locale_setting = use_synced_storage::<LocalStorage, _>("locale".to_string(), || None);
+        // NOTE: SSG pages are rendered in english, so check the browser after hydration.
+        use_effect(move || {
+            let locale = locale_setting()
+                .or_else(|| utils::browser_language().and_then(|tag| Locale::from_tag(&tag)))
+                .unwrap_or_default();
+            *i18n::LOCALE.write() = locale;
+            utils::set_document_language(locale.code());
+        });
+        let mut entitlements = use_signal(Entitlements::default);
+        use_effect(move || {
+            let user_id = auth_state.user_id();
+            spawn(async move {
+                let fetched = match user_id {
+                    Some(user_id) => Entitlements::fetch(user_id).await,
+                    None => Ok(Entitlements::default()),
+                };
+                match fetched {
+                    Ok(fetched) => entitlements.set(fetched),
+                    Err(e) => tracing::warn!("Failed to fetch entitlements: {e}"),
+                }
+            });
+        });
+        use_hook(offline::register_service_worker);
+        use_future(offline::watch_connectivity);
+        // Warm the offline caches once the app has loaded
+        use_effect(move || {
+            let learned = stats.peek().birds_learned();
+            spawn(offline::cache_for_offline(learned));
+        });
+        use_context_provider(|| Self {
+            auth_state,
+            stats,
+            locale_setting,
+            entitlements,
+        });
     }
 }
 
@@ -43,11 +90,31 @@ pub fn App() -> Element {
         }
         head::Link { rel: "stylesheet",  href: ARCADE_FONT }
         head::Link { rel: "stylesheet",  href: MONO_FONT }
+        head::Link { rel: "manifest", href: "/manifest.webmanifest" }
         Router::<Route> {
         }
     }
 }
 
+/// Shown instead of the app when its configuration is invalid, see [`conf::init`].
+pub fn InvalidConfig() -> Element {
+    rsx! {
+        head::Link {
+            rel: "stylesheet",
+            href: asset!("assets/tailwind.css"),
+        }
+        div {
+            class: "text-red-dark text-center flex flex-col items-center justify-center gap-6 mt-8",
+            div { class: "text-3xl", {t!("uh-oh")} }
+            div { class: "text-lg", {t!("error-invalid-config")} }
+            code {
+                class: "select-all",
+                "{conf::init_error().unwrap_or_default()}"
+            }
+        }
+    }
+}
+
 #[derive(Clone, Routable, Debug, PartialEq)]
 #[rustfmt::skip]
 enum Route {
@@ -67,6 +134,23 @@ enum Route {
 
         #[route("/birds")]
         Birds {},
+
+        #[route("/packs")]
+        Packs {},
+
+        #[route("/progress")]
+        Progress {},
+
+        #[route("/trophies")]
+        Trophies {},
+
+        #[route("/archive")]
+        Archive {},
+
+        #[route("/archive/:day")]
+        PlayArchived {
+            day: NaiveDate,
+        },
 }
 
 #[component]
@@ -85,15 +169,16 @@ fn HeaderFooter() -> Element {
                 id: "footer",
                 class: "h-6 shrink grow-0 mt-auto hidden sm:flex justify-center items-center",
                 div {
-                    "© 2024 birdtalk"
+                    {t!("copyright")}
                 }
                 Link {
                     to: "https://github.com/samtay/birdtalk",
                     new_tab: true,
                     class: "ml-2",
                     icons::Github {}
-                    span { class: "sr-only", "GitHub Repository" }
+                    span { class: "sr-only", {t!("github-repository")} }
                 }
+                LocaleSelect {}
             }
         }
     }
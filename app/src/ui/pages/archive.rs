@@ -0,0 +1,156 @@
+//! Past daily packs, a month at a time, so that missed days can still be played. They're worth XP
+//! like any other pack, but don't count toward streaks (see [`crate::stats`]).
+
+use std::collections::{BTreeSet, HashSet};
+
+use chrono::{Datelike, Months, NaiveDate};
+use dioxus::prelude::*;
+
+use crate::{
+    clock::Clock,
+    pack::PackIdentifier,
+    t,
+    ui::{AppCtx, Route},
+};
+
+#[component]
+pub fn Archive() -> Element {
+    let stats = use_context::<AppCtx>().stats;
+    // NOTE: SSG pages are rendered without the user's stats, so only read them after hydration.
+    let mut completed = use_signal(HashSet::new);
+    let mut completed_late = use_signal(BTreeSet::new);
+    let mut today = use_signal(|| Clock::System.today());
+    use_effect(move || {
+        let stats = stats.read();
+        completed.set(stats.daily_packs_completed().iter().copied().collect());
+        completed_late.set(stats.late_daily_packs_completed().clone());
+        today.set(stats.today());
+    });
+    let mut month = use_signal(|| first_of_month(today()));
+    let is_current_month = month() >= first_of_month(today());
+
+    rsx! {
+        div {
+            class: "max-w-screen-sm mx-auto p-4 sm:p-8 flex flex-col gap-6",
+            h2 { class: "text-3xl sm:text-4xl text-center uppercase", {t!("daily-pack-archive")} }
+            div { class: "text-lg text-center", {t!("daily-pack-archive-intro")} }
+            div {
+                class: "flex flex-row justify-between items-center",
+                button {
+                    class: "px-4 py-1 border-2 border-purple-dark focus:outline-none focus-visible:ring focus-visible:ring-purple-dark font-semibold text-purple-dark rounded-xl sm:hover:bg-purple-dark sm:hover:text-white",
+                    onclick: move |_| month.set(month() - Months::new(1)),
+                    {t!("previous-month")}
+                }
+                h3 {
+                    class: "text-xl font-semibold",
+                    {t!("archive-month", month = month().month(), year = month().year())}
+                }
+                button {
+                    class: "px-4 py-1 border-2 border-purple-dark focus:outline-none focus-visible:ring focus-visible:ring-purple-dark font-semibold text-purple-dark rounded-xl sm:enabled:hover:bg-purple-dark sm:enabled:hover:text-white disabled:opacity-50",
+                    disabled: is_current_month,
+                    onclick: move |_| month.set(month() + Months::new(1)),
+                    {t!("next-month")}
+                }
+            }
+            ol {
+                class: "grid grid-cols-7 gap-2",
+                // pad the first week, which starts on Monday
+                for ix in 0..month().weekday().num_days_from_monday() {
+                    li { key: "padding-{ix}" }
+                }
+                for day in days_of_month(month()) {
+                    ArchiveDay {
+                        key: "{day}",
+                        day,
+                        today: today(),
+                        completed: completed.read().contains(&day),
+                        completed_late: completed_late.read().contains(&day),
+                    }
+                }
+            }
+            div {
+                class: "flex flex-row flex-wrap gap-4 text-sm",
+                span {
+                    span { class: "inline-block w-3 h-3 rounded-sm bg-green-dark mr-1" }
+                    {t!("calendar-daily-pack")}
+                }
+                span {
+                    span { class: "inline-block w-3 h-3 rounded-sm bg-green mr-1" }
+                    {t!("calendar-completed-late")}
+                }
+            }
+        }
+    }
+}
+
+/// A day of the archive, which links to its daily pack unless it's yet to come. Today's pack is
+/// played as usual, since it's still on time.
+#[component]
+fn ArchiveDay(day: NaiveDate, today: NaiveDate, completed: bool, completed_late: bool) -> Element {
+    let mut class = if completed {
+        "bg-green-dark text-white"
+    } else if completed_late {
+        "bg-green"
+    } else {
+        "bg-offwhite-2"
+    }
+    .to_string();
+    if day == today {
+        class += " border-2 border-purple-dark";
+    }
+    let route = if day == today {
+        Route::Play {
+            pack_id: PackIdentifier::Date(day),
+        }
+    } else {
+        Route::PlayArchived { day }
+    };
+    rsx! {
+        li {
+            if day > today {
+                div {
+                    class: "p-2 rounded-xl text-center text-black/30",
+                    "{day.day()}"
+                }
+            } else {
+                Link {
+                    class: "block p-2 rounded-xl text-center font-semibold outline-none focus-visible:ring sm:hover:ring sm:hover:ring-purple-dark {class}",
+                    to: route,
+                    span { "aria-hidden": "true", "{day.day()}" }
+                    span { class: "sr-only", {t!("play-daily-pack-of", date = day.to_string())} }
+                }
+            }
+        }
+    }
+}
+
+fn first_of_month(day: NaiveDate) -> NaiveDate {
+    day.with_day(1).unwrap()
+}
+
+/// Every day of the month starting on `first`.
+fn days_of_month(first: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    let next = first + Months::new(1);
+    first.iter_days().take((next - first).num_days() as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn lists_every_day_of_the_month() {
+        let days = |first: &str| days_of_month(first.parse().unwrap()).collect::<Vec<_>>();
+        assert_eq!(days("2024-02-01").len(), 29);
+        assert_eq!(days("2023-02-01").len(), 28);
+        let december = days("2024-12-01");
+        assert_eq!(december.len(), 31);
+        assert_eq!(december.last().unwrap().day(), 31);
+        assert_eq!(
+            first_of_month("2024-12-25".parse().unwrap()) - Duration::days(1),
+            "2024-11-30".parse::<NaiveDate>().unwrap()
+        );
+    }
+}
@@ -1,24 +1,32 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use dioxus::prelude::*;
 
 use crate::{
     bird::Bird,
+    datasource::BirdPages,
+    i18n::{self, LOCALE},
     pack::{Pack, PackIdentifier},
+    supabase, t,
     ui::{
         components::{
             icons::{CheckedCircle, UncheckedCircle},
-            BirdCard, BirdCardPlaceholder,
+            BirdCard, BirdCardPlaceholder, RetryButton,
         },
         pages::PLAY_STATUS,
         AppCtx, Route,
     },
+    utils,
 };
 
 // TODO: save these settings in local storage
 static SIMULTANEOUS_CALLS: GlobalSignal<usize> = Signal::global(|| 1);
 static LOOP_AUDIO: GlobalSignal<bool> = Signal::global(|| true);
 const MINIMUM_BIRDS: usize = 10;
+/// Number of birds to fetch at a time while scrolling the aviary
+const BIRD_PAGE_SIZE: usize = 30;
+/// Number of most confused pairs of birds to list
+const CONFUSED_PAIRS_SHOWN: usize = 5;
 
 #[derive(Clone, Copy)]
 struct AviaryCtx {
@@ -75,25 +83,23 @@ pub fn Birds() -> Element {
 
 #[component]
 fn EmptyNest() -> Element {
+    let message = t!("play-to-get-started", link = i18n::ELEMENT);
+    let (before, after) = i18n::split_at_element(&message);
     rsx! {
         div {
             class: "text-center flex flex-col items-center justify-center gap-6 mt-8",
-            div { class: "text-3xl", "An empty nest 🪹" }
+            div { class: "text-3xl", {t!("empty-nest")} }
             div {
                 class: "text-lg",
                 span {
-                    "You need to learn more birds to fill out your aviary!"
+                    {t!("empty-nest-body")}
                 }
             }
             div {
                 class: "text-lg",
-                span {
-                    "Play the "
-                }
+                span { "{before}" }
                 PackOfTheDayLink {}
-                span {
-                    " to get started!"
-                }
+                span { "{after}" }
             }
         }
     }
@@ -109,14 +115,14 @@ fn Sidebar() -> Element {
     let num_selected = use_memo(move || selected.read().len());
     let review_disabled = use_memo(move || num_selected() < MINIMUM_BIRDS);
     let select_to_review_text = use_memo(move || match num_selected() {
-        0 => format!("Select {MINIMUM_BIRDS} birds to review"),
-        x if x > 0 && x < MINIMUM_BIRDS - 1 => {
-            format!("Select {} more birds", MINIMUM_BIRDS - x)
-        }
-        x if x == MINIMUM_BIRDS - 1 => "Select 1 more bird".to_string(),
+        0 => t!("select-birds-to-review", count = MINIMUM_BIRDS),
+        x if x < MINIMUM_BIRDS => t!("select-more-birds", count = MINIMUM_BIRDS - x),
         _ => "".to_string(),
     });
 
+    let intro = t!("aviary-intro", link = i18n::ELEMENT);
+    let (intro_before, intro_after) = i18n::split_at_element(&intro);
+
     // Ugh SSG is killing me
     let mut enough_birds = use_signal(|| false);
     use_effect(move || {
@@ -128,23 +134,35 @@ fn Sidebar() -> Element {
             class: "text-center sm:text-left text-lg flex flex-col gap-4 sm:max-w-xs",
             h2 {
                 class: "text-3xl",
-                "Your Aviary"
+                {t!("your-aviary")}
             }
             div {
-                span {
-                    "Here are all the birds you've learned so far! 🐦 Continue to play the "
-                }
+                span { "{intro_before}" }
                 PackOfTheDayLink {}
-                span {
-                    " to learn more!"
-                }
+                span { "{intro_after}" }
+            }
+            Link {
+                class: "font-semibold underline text-purple-dark outline-none focus-visible:ring sm:hover:text-white sm:hover:bg-purple-dark",
+                to: Route::Progress {},
+                {t!("see-your-progress")}
+            }
+            Link {
+                class: "font-semibold underline text-purple-dark outline-none focus-visible:ring sm:hover:text-white sm:hover:bg-purple-dark",
+                to: Route::Trophies {},
+                {t!("see-your-trophies")}
+            }
+            Link {
+                class: "font-semibold underline text-purple-dark outline-none focus-visible:ring sm:hover:text-white sm:hover:bg-purple-dark",
+                to: Route::Archive {},
+                {t!("see-the-archive")}
             }
             div {
                 class: if enough_birds() {
                     "hidden"
                 },
-                "Once you've learned {MINIMUM_BIRDS} birds, come back here to play a round of review!"
+                {t!("aviary-review-locked", count = MINIMUM_BIRDS)}
             }
+            ConfusedPairs {}
             div {
                 class: "fixed bottom-0 left-0 right-0 z-10 p-2 pb-4 border-t bg-offwhite sm:static sm:mt-auto flex flex-col gap-2 items-center",
                 class: if !enough_birds() {
@@ -164,7 +182,65 @@ fn Sidebar() -> Element {
                         *PLAY_STATUS.write() = Some(pack);
                         navigator().push(Route::Play {pack_id});
                     },
-                    "review"
+                    {t!("review")}
+                }
+            }
+        }
+    }
+}
+
+/// The birds the user mixes up the most, with a review of them.
+#[component]
+fn ConfusedPairs() -> Element {
+    let stats = use_context::<AppCtx>().stats;
+    let pairs = use_memo(move || {
+        let mut pairs = stats.read().most_confused_pairs();
+        pairs.truncate(CONFUSED_PAIRS_SHOWN);
+        pairs
+    });
+    let review = use_memo(move || PackIdentifier::confused_review(&stats.read(), MINIMUM_BIRDS));
+    let birds = use_resource(move || {
+        let bird_ids: Vec<u64> = pairs
+            .read()
+            .iter()
+            .flat_map(|pair| [pair.birds.0, pair.birds.1])
+            .collect();
+        Bird::fetch_by_ids(bird_ids)
+    });
+
+    let names: HashMap<u64, String> = match &*birds.read_unchecked() {
+        Some(Ok(birds)) if !birds.is_empty() => birds
+            .iter()
+            .map(|b| (b.id, b.common_name_for(LOCALE.read().code()).to_string()))
+            .collect(),
+        _ => return rsx! {},
+    };
+    let name = |bird_id| names.get(&bird_id).cloned().unwrap_or_default();
+    rsx! {
+        div {
+            class: "flex flex-col gap-2",
+            h3 { class: "text-xl", {t!("most-confused-pairs")} }
+            ul {
+                class: "text-base flex flex-col gap-1",
+                for pair in pairs() {
+                    li {
+                        key: "{pair.birds.0}-{pair.birds.1}",
+                        class: "flex flex-row justify-between gap-2",
+                        span { "{name(pair.birds.0)} ↔ {name(pair.birds.1)}" }
+                        span {
+                            class: "font-semibold whitespace-nowrap",
+                            {t!("times-confused", count = pair.times)}
+                        }
+                    }
+                }
+            }
+            if let Some(pack_id) = review() {
+                button {
+                    class: "self-center px-6 py-2 border-2 border-purple-dark focus:outline-none focus-visible:ring focus-visible:ring-purple-dark font-semibold text-base text-purple-dark rounded-xl sm:hover:bg-purple-dark sm:hover:text-white uppercase",
+                    onclick: move |_| {
+                        navigator().push(Route::Play { pack_id: pack_id.clone() });
+                    },
+                    {t!("review-confused")}
                 }
             }
         }
@@ -183,52 +259,102 @@ fn BirdCollection() -> Element {
     }
 }
 
-// TODO: paginate! Use scroll events to load more birds.
-// Can probably do something nice where birds being fetched are placeholder cards and then they fill in (maybe a hashmap of Options?)
 #[component]
 fn BirdGrid() -> Element {
     let bird_ids = use_context::<AviaryCtx>().bird_ids;
+    // Taken while a page is being fetched
+    let mut pages = use_signal(|| None::<BirdPages>);
+    let mut birds = use_signal(Vec::new);
+    let mut error = use_signal(|| None::<supabase::Error>);
+    let mut loading = use_signal(|| false);
+    let mut done = use_signal(|| false);
+    // Bumped whenever the learned birds change, so that stale pages are dropped
+    let mut generation = use_signal(|| 0);
+
+    let mut load_more = move || {
+        let Some(mut pager) = pages.take() else {
+            return;
+        };
+        let current = *generation.peek();
+        loading.set(true);
+        spawn(async move {
+            let page = pager.next_page().await;
+            if *generation.peek() != current {
+                return;
+            }
+            match page {
+                Some(Ok(page)) => birds.write().extend(page),
+                Some(Err(e)) => error.set(Some(e)),
+                None => {}
+            }
+            done.set(pager.is_done());
+            pages.set(Some(pager));
+            loading.set(false);
+        });
+    };
 
-    let birds =
-        use_resource(
-            move || async move { Bird::fetch_by_ids(bird_ids.read().iter().copied()).await },
-        );
+    use_effect(move || {
+        let ids = bird_ids.read().clone();
+        *generation.write() += 1;
+        birds.set(Vec::new());
+        error.set(None);
+        done.set(false);
+        pages.set(Some(Bird::pages_by_ids(ids, BIRD_PAGE_SIZE)));
+        load_more();
+    });
 
-    match &*birds.read_unchecked() {
-        None => rsx! { BirdsPlaceholder {bird_ids} },
-        Some(Ok(birds)) => rsx! { BirdsInner {birds: birds.clone()} },
-        Some(Err(e)) => rsx! {
+    if let Some(e) = &*error.read() {
+        let report = t!("report-error", link = i18n::ELEMENT);
+        let (before, after) = i18n::split_at_element(&report);
+        return rsx! {
             div {
                 class: "text-red-dark text-center flex flex-col items-center justify-center gap-6 mb-auto",
-                div { class: "text-3xl", "Uh oh! 😱" }
+                div { class: "text-3xl", {t!("uh-oh")} }
                 div {
                     class: "text-lg",
-                    span {
-                        "Something went wrong fetching your birds! Please open a "
-                    }
+                    span { {t!("error-fetching-birds")} " " "{before}" }
                     a {
                         class: "underline text-purple-dark",
                         href: "https://github.com/samtay/birdtalk/issues/new",
                         target: "_blank",
-                        "GitHub issue"
+                        {t!("github-issue")}
                     }
-                    span { " with the following error:" }
+                    span { "{after}" }
                 }
                 div {
                     code {
                         class: "select-all",
-                        "{e}"
+                        "{e.localized()}"
+                    }
+                }
+                RetryButton {
+                    onretry: move |_| {
+                        error.set(None);
+                        load_more();
                     }
                 }
             }
-        },
+        };
+    }
+
+    if birds.read().is_empty() && !done() {
+        rsx! { BirdsPlaceholder {bird_ids} }
+    } else {
+        rsx! {
+            BirdsInner {
+                birds: birds(),
+                if !loading() && !done() {
+                    ScrollSentinel { onvisible: move |_| load_more() }
+                }
+            }
+        }
     }
 }
 
 const BIRD_GRID_HEIGHT: &str = "sm:h-[calc(100vh-120px)]";
 
 #[component]
-fn BirdsInner(birds: Vec<Bird>) -> Element {
+fn BirdsInner(birds: Vec<Bird>, children: Element) -> Element {
     // NOTE: might be better to use form values with a memo
     let AviaryCtx { enough_birds, .. } = use_context();
     rsx! {
@@ -241,6 +367,24 @@ fn BirdsInner(birds: Vec<Bird>) -> Element {
             for bird in birds {
                 BirdInner { bird }
             }
+            {children}
+        }
+    }
+}
+
+/// Calls `onvisible` once scrolled into view, e.g. to load more items.
+#[component]
+fn ScrollSentinel(onvisible: EventHandler) -> Element {
+    rsx! {
+        li {
+            class: "col-span-full h-px",
+            "aria-hidden": "true",
+            onmounted: move |mnt| async move {
+                if let Some(element) = mnt.downcast::<web_sys::Element>().cloned() {
+                    utils::scrolled_into_view(&element).await;
+                    onvisible.call(());
+                }
+            },
         }
     }
 }
@@ -253,6 +397,14 @@ fn BirdInner(bird: Bird) -> Element {
         ..
     } = use_context();
     let id = bird.id;
+    let stats = use_context::<AppCtx>().stats;
+    let average_response_time_ms = use_memo(move || {
+        stats
+            .read()
+            .bird_stats()
+            .get(&id)
+            .and_then(|bs| bs.average_response_time_ms())
+    });
     let check_icon_class = if !enough_birds() { "hidden" } else { "" };
     let bird_card_class = if enough_birds() {
         "sm:hover:shadow-lg"
@@ -292,6 +444,12 @@ fn BirdInner(bird: Bird) -> Element {
                     bird: bird.clone(),
                     extra_classes: "w-full h-full bg-yellow shadow {bird_card_class} peer-checked:bg-green peer-checked:border-green-dark peer-checked:text-green-extra-dark peer-focus-visible:ring peer-focus-visible:ring-yellow-dark peer-checked:peer-focus-visible:ring-green-dark",
                     text_selection: false,
+                    if let Some(ms) = average_response_time_ms() {
+                        div {
+                            class: "text-sm",
+                            {t!("average-recognition-time", seconds = format!("{:.1}", ms as f64 / 1000.0))}
+                        }
+                    }
                 }
                 CheckedCircle {
                     extra_classes: "text-green-extra-dark inline-block absolute top-2 right-2 sm:top-auto sm:bottom-2 sm:right-[calc(50%-0.75rem)] invisible peer-checked:visible {check_icon_class}",
@@ -337,7 +495,7 @@ fn PackOfTheDayLink() -> Element {
         Link {
             class: "font-semibold underline text-purple-dark outline-none focus-visible:ring sm:hover:text-white sm:hover:bg-purple-dark",
             to: Route::Play { pack_id: PackIdentifier::default() },
-            "Pack of the Day"
+            {t!("pack-of-the-day")}
         }
     }
 }
@@ -1,6 +1,9 @@
 use dioxus::prelude::*;
 
-use crate::ui::components::PackOfTheDay;
+use crate::{
+    t,
+    ui::{components::PackOfTheDay, Route},
+};
 
 #[component]
 pub fn Index() -> Element {
@@ -14,9 +17,14 @@ pub fn Index() -> Element {
                     class: "lg:ml-auto lg:max-w-[512px] flex flex-col justify-between gap-5",
                     div {
                         class: "text-3xl sm:text-4xl text-center uppercase",
-                        "10 new birds every day"
+                        {t!("tagline-daily")}
                     }
                     PackOfTheDay { }
+                    Link {
+                        class: "text-center underline text-purple-dark",
+                        to: Route::Packs {},
+                        {t!("browse-packs")}
+                    }
                 }
             }
             div {
@@ -26,7 +34,7 @@ pub fn Index() -> Element {
                     class: "lg:mr-auto lg:ml-48 lg:max-w-[512px] uppercase",
                     div {
                         class: "w-full sm:w-56 text-4xl sm:text-5xl text-left text-bold leading-normal sm:leading-tight",
-                        "A game that helps you memorize bird calls."
+                        {t!("tagline-game")}
                     }
                 }
             }
@@ -36,11 +44,11 @@ pub fn Index() -> Element {
                     class: "max-w-screen-lg mx-auto",
                     span {
                         class: "text-4xl sm:text-5xl",
-                        "The wild speaks. "
+                        {t!("wild-speaks")} " "
                     }
                     span {
                         class: "text-3xl",
-                        "One of the best ways to spot a bird is to hear it first. Learn to recognize new calls here for your next adventure out in the field."
+                        {t!("wild-speaks-body")}
                     }
                 }
             }
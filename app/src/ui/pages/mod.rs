@@ -1,9 +1,17 @@
 //! Pages aka routes
 
+mod archive;
 mod birds;
 mod index;
+mod packs;
 mod play;
+mod progress;
+mod trophies;
 
+pub use archive::*;
 pub use birds::*;
 pub use index::*;
+pub use packs::*;
 pub use play::*;
+pub use progress::*;
+pub use trophies::*;
@@ -0,0 +1,106 @@
+use dioxus::prelude::*;
+
+use crate::{
+    bird::BirdPack,
+    pack::Pack,
+    payments::Product,
+    t,
+    ui::{
+        components::{icons::LockIcon, RetryButton, UpgradeButton},
+        pages::PLAY_STATUS,
+        AppCtx, Route,
+    },
+};
+
+/// Every pack other than the daily ones, with premium packs locked until they're paid for.
+#[component]
+pub fn Packs() -> Element {
+    let ctx = use_context::<AppCtx>();
+    let mut packs = use_resource(BirdPack::fetch_all);
+    let packs = match &*packs.read_unchecked() {
+        None => return rsx! { PacksPlaceholder {} },
+        Some(Ok(packs)) => packs.clone(),
+        Some(Err(e)) => {
+            return rsx! {
+                div {
+                    class: "text-red-dark text-center flex flex-col items-center justify-center gap-6 mb-auto mt-4",
+                    div { class: "text-3xl", {t!("uh-oh")} }
+                    div { class: "text-lg", {t!("error-fetching-packs")} }
+                    div { code { class: "select-all", "{e.localized()}" } }
+                    RetryButton { onretry: move |_| packs.restart() }
+                }
+            }
+        }
+    };
+    let any_locked = packs
+        .iter()
+        .any(|pack| !ctx.entitlements.read().unlocks(pack));
+    rsx! {
+        div {
+            class: "max-w-screen-lg mx-auto p-4 sm:p-8 flex flex-col gap-6",
+            h2 { class: "text-3xl sm:text-4xl text-center uppercase", {t!("packs")} }
+            ul {
+                class: "grid grid-cols-1 sm:grid-cols-2 gap-4",
+                for pack in packs {
+                    PackTile {
+                        key: "{pack.id}",
+                        unlocked: ctx.entitlements.read().unlocks(&pack),
+                        pack,
+                    }
+                }
+            }
+            if any_locked {
+                div {
+                    class: "flex flex-col items-center gap-2 text-center",
+                    div { class: "text-lg", {t!("premium-pitch")} }
+                    UpgradeButton { product: Product::AllPacks }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PackTile(pack: BirdPack, unlocked: bool) -> Element {
+    let count = pack.birds.len();
+    rsx! {
+        li {
+            class: "border rounded-xl shadow p-4 flex flex-col gap-2",
+            class: if unlocked { "bg-yellow" } else { "bg-offwhite-2 text-black/60" },
+            div {
+                class: "flex flex-row justify-between items-center gap-2",
+                h3 { class: "text-xl font-semibold", "{pack.name}" }
+                if !unlocked {
+                    LockIcon {}
+                    span { class: "sr-only", {t!("locked")} }
+                }
+            }
+            div { "{pack.description}" }
+            div { class: "text-sm", {t!("pack-bird-count", count = count)} }
+            if unlocked {
+                button {
+                    class: "self-end px-6 py-2 border-2 border-green-extra-dark focus:outline-none focus-visible:ring focus-visible:ring-green-dark font-semibold bg-green-dark text-white rounded-xl shadow sm:hover:shadow-xl sm:hover:scale-110 transition-transform uppercase",
+                    onclick: move |_| {
+                        // fetched again to be played, with its media signed
+                        let pack_id = Pack::from(pack.clone()).id;
+                        *PLAY_STATUS.write() = None;
+                        navigator().push(Route::Play { pack_id });
+                    },
+                    {t!("play")}
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PacksPlaceholder() -> Element {
+    rsx! {
+        div {
+            class: "max-w-screen-lg mx-auto p-4 sm:p-8 grid grid-cols-1 sm:grid-cols-2 gap-4 animate-pulse",
+            for _ in 0..4 {
+                div { class: "h-36 bg-offwhite-2 border border-black/10 rounded-xl" }
+            }
+        }
+    }
+}
@@ -1,8 +1,12 @@
+use chrono::NaiveDate;
 use dioxus::prelude::*;
 
 use crate::{
     pack::{Pack, PackIdentifier},
+    supabase::Error,
+    t,
     ui::{
+        components::{LockedPack, RetryButton},
         game::{GameView, GameViewPlaceholder},
         AppCtx,
     },
@@ -12,6 +16,18 @@ pub static PLAY_STATUS: GlobalSignal<Option<Pack>> = Signal::global(|| None);
 
 #[component]
 pub fn Play(pack_id: PackIdentifier) -> Element {
+    rsx! { PlayPack { pack_id, from_archive: false } }
+}
+
+/// A past daily pack, played from the archive. These are only worth XP, so that missed days can't
+/// be made up to keep a streak going.
+#[component]
+pub fn PlayArchived(day: NaiveDate) -> Element {
+    rsx! { PlayPack { pack_id: PackIdentifier::Date(day), from_archive: true } }
+}
+
+#[component]
+fn PlayPack(pack_id: PackIdentifier, from_archive: bool) -> Element {
     // Do I need reactivity on pack_id? https://docs.rs/dioxus-hooks/0.6.0-alpha.2/dioxus_hooks/fn.use_effect.html#with-non-reactive-dependencies
     let pack_id = use_hook(|| CopyValue::new(pack_id));
 
@@ -24,28 +40,24 @@ pub fn Play(pack_id: PackIdentifier) -> Element {
             .filter(|p| p.id == *pack_id.read())
             .cloned()
     });
-    let mut error = use_signal(|| None);
+    let mut error = use_signal(|| None::<Error>);
+    // Bumped to fetch the pack again after an error
+    let mut attempts = use_signal(|| 0);
 
     // But if not (perhaps a fresh page load on this route),
     let ctx = use_context::<AppCtx>();
     use_effect(move || {
+        let _ = attempts.read();
         if pack_to_play.read().is_none() {
             spawn(async move {
                 let pack_id = pack_id.read();
                 let stats = ctx.stats.read();
 
-                // Enforce ad-hoc review rounds are based on birds already learned.
+                // Enforce ad-hoc review rounds are based on birds already learned (or confused).
                 if let PackIdentifier::Birds(ref birds_requested) = *pack_id {
                     for bird_id in birds_requested {
-                        if stats
-                            .bird_stats()
-                            .get(bird_id)
-                            .filter(|b| b.learned)
-                            .is_none()
-                        {
-                            error.set(Some(
-                                "You haven't learned some of these birds yet!".to_string(),
-                            ));
+                        if !stats.can_review(*bird_id) {
+                            error.set(Some(t!("error-birds-not-learned").into()));
                             return;
                         }
                     }
@@ -54,29 +66,42 @@ pub fn Play(pack_id: PackIdentifier) -> Element {
                 let result = Pack::fetch_by_id(&pack_id).await;
                 match result {
                     Ok(pack) => *PLAY_STATUS.write() = Some(pack),
-                    Err(e) => error.set(Some(format!("{e}"))),
+                    Err(e) => error.set(Some(e)),
                 }
             });
         }
     });
 
     match (pack_to_play(), error()) {
-        (Some(pack), _) => rsx! { GameView { pack } },
-        (_, Some(error)) => rsx! { ErrorView { error_msg: "{error}" } },
+        (Some(pack), _) if !ctx.entitlements.read().unlocks_pack(&pack) => rsx! { LockedPack {} },
+        (Some(pack), _) => rsx! { GameView { pack, from_archive } },
+        (_, Some(Error::ErrorMessage(msg))) => rsx! { ErrorView { error_msg: msg } },
+        (_, Some(e)) => rsx! {
+            ErrorView {
+                error_msg: e.localized(),
+                onretry: move |_| {
+                    error.set(None);
+                    *attempts.write() += 1;
+                },
+            }
+        },
         _ => rsx! { GameViewPlaceholder {}},
     }
 }
 
 #[component]
-fn ErrorView(error_msg: String) -> Element {
+fn ErrorView(error_msg: String, onretry: Option<EventHandler>) -> Element {
     rsx! {
         div {
             class: "text-red-dark text-center flex flex-col items-center justify-center gap-6 mb-auto mt-4",
-            div { class: "text-3xl", "Uh oh! 😱" }
+            div { class: "text-3xl", {t!("uh-oh")} }
             div {
                 class: "text-lg",
                 "{error_msg}"
             }
+            if let Some(onretry) = onretry {
+                RetryButton { onretry }
+            }
         }
     }
 }
@@ -0,0 +1,298 @@
+//! The user's progress over time, charted from their stats log (see [`crate::stats`]).
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, Duration, NaiveDate};
+use chrono_tz::TZ_VARIANTS;
+use dioxus::prelude::*;
+
+use crate::{
+    bird::Bird,
+    clock::Clock,
+    stats::{DailyPackStreak, DayProgress, Stats, STREAK_FREEZE_DAYS},
+    supabase, t,
+    ui::{components::LineChart, AppCtx},
+};
+
+/// Number of weeks shown in the daily pack calendar
+const CALENDAR_WEEKS: i64 = 15;
+/// Size of each day in the daily pack calendar, and the gap between them
+const CALENDAR_CELL: i64 = 10;
+const CALENDAR_GAP: i64 = 2;
+/// Number of birds to fetch at a time, to group them by family
+const BIRD_PAGE_SIZE: usize = 30;
+
+#[component]
+pub fn Progress() -> Element {
+    let stats = use_context::<AppCtx>().stats;
+    // NOTE: SSG pages are rendered without the user's stats, so only read them after hydration.
+    let mut history = use_signal(Vec::new);
+    let mut daily_packs = use_signal(Vec::new);
+    let mut streak = use_signal(DailyPackStreak::default);
+    let mut today = use_signal(|| Clock::System.today());
+    use_effect(move || {
+        let stats = stats.read();
+        history.set(stats.history());
+        daily_packs.set(stats.daily_packs_completed().to_vec());
+        streak.set(stats.daily_pack_streak());
+        today.set(stats.today());
+    });
+
+    rsx! {
+        div {
+            class: "max-w-screen-lg mx-auto p-4 sm:p-8 flex flex-col gap-8",
+            h2 { class: "text-3xl sm:text-4xl text-center uppercase", {t!("your-progress")} }
+            if history.read().is_empty() {
+                div { class: "text-lg text-center", {t!("no-progress-yet")} }
+            } else {
+                div {
+                    class: "grid grid-cols-1 sm:grid-cols-2 gap-8",
+                    BirdsLearnedChart { history }
+                    AccuracyChart { history }
+                    DailyPackCalendar { history, daily_packs, streak, today }
+                    FamilyMastery {}
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn BirdsLearnedChart(history: ReadOnlySignal<Vec<DayProgress>>) -> Element {
+    let history = history.read();
+    let (Some(first), Some(last)) = (history.first(), history.last()) else {
+        return rsx! {};
+    };
+    let values: Vec<f64> = history.iter().map(|d| d.birds_learned as f64).collect();
+    rsx! {
+        LineChart {
+            title: t!("chart-birds-learned", count = last.birds_learned),
+            max: values.iter().copied().fold(1.0, f64::max),
+            values,
+            start_label: first.date.to_string(),
+            end_label: last.date.to_string(),
+        }
+    }
+}
+
+#[component]
+fn AccuracyChart(history: ReadOnlySignal<Vec<DayProgress>>) -> Element {
+    let history = history.read();
+    // days with only a daily pack completed have no answers to be accurate about
+    let answered: Vec<&DayProgress> = history.iter().filter(|d| d.answers > 0).collect();
+    let (Some(first), Some(last)) = (answered.first(), answered.last()) else {
+        return rsx! {};
+    };
+    let percent = |day: &DayProgress| (day.accuracy() * 100.0).round();
+    rsx! {
+        LineChart {
+            title: t!("chart-accuracy", percent = percent(last)),
+            values: answered.iter().map(|d| percent(d)).collect::<Vec<_>>(),
+            max: 100.0,
+            start_label: first.date.to_string(),
+            end_label: last.date.to_string(),
+        }
+    }
+}
+
+/// The last few weeks, a column per week, marking the days that were played, the days whose
+/// daily pack was completed and the days a streak freeze was used for.
+#[component]
+fn DailyPackCalendar(
+    history: ReadOnlySignal<Vec<DayProgress>>,
+    daily_packs: ReadOnlySignal<Vec<NaiveDate>>,
+    streak: ReadOnlySignal<DailyPackStreak>,
+    today: ReadOnlySignal<NaiveDate>,
+) -> Element {
+    let played: HashSet<NaiveDate> = history.read().iter().map(|d| d.date).collect();
+    let completed: HashSet<NaiveDate> = daily_packs.read().iter().copied().collect();
+    let frozen: HashSet<NaiveDate> = streak.read().frozen.iter().copied().collect();
+    let freezes = streak.read().freezes;
+    let today = today();
+    let first_monday = today
+        - Duration::days(today.weekday().num_days_from_monday().into())
+        - Duration::weeks(CALENDAR_WEEKS - 1);
+    let days = (0..CALENDAR_WEEKS * 7)
+        .map(|ix| (ix, first_monday + Duration::days(ix)))
+        .take_while(|(_, date)| *date <= today);
+    let size = |count: i64| count * (CALENDAR_CELL + CALENDAR_GAP) - CALENDAR_GAP;
+    let fill = |date: &NaiveDate| {
+        if completed.contains(date) {
+            "fill-green-dark"
+        } else if frozen.contains(date) {
+            "fill-purple-dark"
+        } else if played.contains(date) {
+            "fill-green"
+        } else {
+            "fill-black/10"
+        }
+    };
+    rsx! {
+        figure {
+            class: "flex flex-col gap-2",
+            figcaption {
+                class: "text-xl",
+                {t!("daily-pack-calendar", count = completed.len())}
+            }
+            svg {
+                class: "w-full max-w-md",
+                view_box: "0 0 {size(CALENDAR_WEEKS)} {size(7)}",
+                xmlns: "http://www.w3.org/2000/svg",
+                for (ix, date) in days {
+                    rect {
+                        key: "{date}",
+                        x: "{ix / 7 * (CALENDAR_CELL + CALENDAR_GAP)}",
+                        y: "{ix % 7 * (CALENDAR_CELL + CALENDAR_GAP)}",
+                        width: "{CALENDAR_CELL}",
+                        height: "{CALENDAR_CELL}",
+                        rx: "2",
+                        class: fill(&date),
+                    }
+                }
+            }
+            div {
+                class: "flex flex-row gap-4 text-sm",
+                span {
+                    span { class: "inline-block w-3 h-3 rounded-sm bg-green mr-1" }
+                    {t!("calendar-played")}
+                }
+                span {
+                    span { class: "inline-block w-3 h-3 rounded-sm bg-green-dark mr-1" }
+                    {t!("calendar-daily-pack")}
+                }
+                span {
+                    span { class: "inline-block w-3 h-3 rounded-sm bg-purple-dark mr-1" }
+                    {t!("calendar-streak-freeze")}
+                }
+            }
+            div {
+                class: "text-sm",
+                {t!("streak-freezes", count = freezes, days = STREAK_FREEZE_DAYS)}
+            }
+            StreakTimeZone {}
+        }
+    }
+}
+
+/// The time zone days are counted in for streaks, which the user can pick, e.g. after moving. It's
+/// the device's until then.
+#[component]
+fn StreakTimeZone() -> Element {
+    let mut stats = use_context::<AppCtx>().stats;
+    // NOTE: the device's time zone is only known after hydration too
+    let mut time_zones = use_signal(|| None);
+    use_effect(move || {
+        let stats = stats.read();
+        time_zones.set(Some((stats.time_zone(), stats.clock().local_time_zone())));
+    });
+    let Some((pinned, local)) = time_zones() else {
+        return rsx! {};
+    };
+    let Some(selected) = pinned.or(local) else {
+        return rsx! {};
+    };
+    rsx! {
+        div {
+            class: "flex flex-row flex-wrap items-center gap-2 text-sm",
+            label { r#for: "streak-time-zone", {t!("streak-time-zone")} }
+            select {
+                id: "streak-time-zone",
+                class: "bg-transparent cursor-pointer outline-none focus-visible:ring focus-visible:ring-purple-dark rounded",
+                onchange: move |e| {
+                    if let Ok(time_zone) = e.value().parse() {
+                        stats.write().pin_time_zone(time_zone);
+                    }
+                },
+                for time_zone in TZ_VARIANTS {
+                    option {
+                        key: "{time_zone.name()}",
+                        value: time_zone.name(),
+                        selected: time_zone == selected,
+                        "{time_zone.name()}"
+                    }
+                }
+            }
+            if let Some(local) = local.filter(|local| pinned != Some(*local)) {
+                button {
+                    class: "px-2 py-1 border-2 border-purple-dark focus:outline-none focus-visible:ring focus-visible:ring-purple-dark font-semibold text-purple-dark rounded-xl sm:hover:bg-purple-dark sm:hover:text-white",
+                    onclick: move |_| stats.write().pin_time_zone(local),
+                    {t!("use-device-time-zone", zone = local.name())}
+                }
+            }
+        }
+    }
+}
+
+/// Of the birds played in each family, how many have been learned.
+#[component]
+fn FamilyMastery() -> Element {
+    let stats = use_context::<AppCtx>().stats;
+    let birds = use_resource(move || {
+        let bird_ids: Vec<u64> = stats.read().bird_stats().keys().copied().collect();
+        async move {
+            let mut pages = Bird::pages_by_ids(bird_ids, BIRD_PAGE_SIZE);
+            let mut birds = Vec::new();
+            while let Some(page) = pages.next_page().await {
+                birds.extend(page?);
+            }
+            Ok::<_, supabase::Error>(birds)
+        }
+    });
+    let families = match &*birds.read_unchecked() {
+        Some(Ok(birds)) => family_mastery(birds, &stats.read()),
+        Some(Err(e)) => {
+            tracing::warn!("Failed to fetch birds for family mastery: {e}");
+            return rsx! {};
+        }
+        None => return rsx! {},
+    };
+    rsx! {
+        figure {
+            class: "flex flex-col gap-2",
+            figcaption { class: "text-xl", {t!("family-mastery")} }
+            ul {
+                class: "flex flex-col gap-2",
+                for (family, learned, played) in families {
+                    li {
+                        key: "{family}",
+                        div {
+                            class: "flex flex-row justify-between gap-2 text-sm",
+                            span { "{family}" }
+                            span { class: "whitespace-nowrap", "{learned} / {played}" }
+                        }
+                        div {
+                            class: "h-2 rounded-full bg-black/10",
+                            div {
+                                class: "h-2 rounded-full bg-green-dark",
+                                style: "width: {learned * 100 / played}%",
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Per family, the number of birds learned and played, most played first.
+fn family_mastery(birds: &[Bird], stats: &Stats) -> Vec<(String, u32, u32)> {
+    let mut families: HashMap<String, (u32, u32)> = HashMap::new();
+    for bird in birds {
+        let family = bird.family.clone().unwrap_or_else(|| t!("unknown-family"));
+        let (learned, played) = families.entry(family).or_default();
+        *played += 1;
+        if stats
+            .bird_stats()
+            .get(&bird.id)
+            .is_some_and(|bs| bs.learned)
+        {
+            *learned += 1;
+        }
+    }
+    let mut families: Vec<_> = families
+        .into_iter()
+        .map(|(family, (learned, played))| (family, learned, played))
+        .collect();
+    families.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    families
+}
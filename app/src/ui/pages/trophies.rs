@@ -0,0 +1,80 @@
+//! Every achievement, and which have been unlocked so far, see [`crate::achievements`].
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Local, Utc};
+use dioxus::prelude::*;
+
+use crate::{
+    achievements::{Achievement, ACHIEVEMENTS},
+    t,
+    ui::{
+        components::icons::{LockIcon, TrophyIcon},
+        AppCtx,
+    },
+};
+
+#[component]
+pub fn Trophies() -> Element {
+    let stats = use_context::<AppCtx>().stats;
+    // NOTE: SSG pages are rendered without the user's stats, so only read them after hydration.
+    let mut unlocked = use_signal(HashMap::new);
+    use_effect(move || {
+        unlocked.set(
+            stats
+                .read()
+                .achievements()
+                .into_iter()
+                .map(|(achievement, at)| (achievement.id, at))
+                .collect(),
+        );
+    });
+
+    rsx! {
+        div {
+            class: "max-w-screen-lg mx-auto p-4 sm:p-8 flex flex-col gap-6",
+            h2 { class: "text-3xl sm:text-4xl text-center uppercase", {t!("trophies")} }
+            div {
+                class: "text-lg text-center",
+                {t!("trophies-unlocked", count = unlocked.read().len(), total = ACHIEVEMENTS.len())}
+            }
+            ul {
+                class: "grid grid-cols-1 sm:grid-cols-2 gap-4",
+                for achievement in ACHIEVEMENTS {
+                    TrophyTile {
+                        key: "{achievement.id}",
+                        achievement,
+                        unlocked_at: unlocked.read().get(achievement.id).copied(),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn TrophyTile(achievement: &'static Achievement, unlocked_at: Option<DateTime<Utc>>) -> Element {
+    rsx! {
+        li {
+            class: "border rounded-xl shadow p-4 flex flex-row items-center gap-4",
+            class: if unlocked_at.is_some() { "bg-yellow" } else { "bg-offwhite-2 text-black/60" },
+            if unlocked_at.is_some() {
+                TrophyIcon {}
+            } else {
+                LockIcon {}
+                span { class: "sr-only", {t!("locked")} }
+            }
+            div {
+                class: "flex flex-col gap-1",
+                h3 { class: "text-xl font-semibold", "{achievement.name()}" }
+                div { "{achievement.description()}" }
+                if let Some(at) = unlocked_at {
+                    div {
+                        class: "text-sm",
+                        {t!("unlocked-on", date = at.with_timezone(&Local).date_naive().to_string())}
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,5 @@
 use std::fmt::Display;
 
-use chrono::NaiveDate;
-
 pub fn join(values: impl IntoIterator<Item = impl Display>, sep: impl Display) -> String {
     use std::fmt::Write;
 
@@ -16,6 +14,89 @@ pub fn join(values: impl IntoIterator<Item = impl Display>, sep: impl Display) -
     s
 }
 
-pub fn today() -> NaiveDate {
-    chrono::offset::Local::now().date_naive()
+/// Percent-encode a value for a URL's query string, like JavaScript's `encodeURIComponent`.
+pub fn encode_uri_component(value: &str) -> String {
+    use std::fmt::Write;
+
+    let mut s = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.!~*'()".contains(&byte) {
+            s.push(byte as char);
+        } else {
+            write!(s, "%{byte:02X}").unwrap();
+        }
+    }
+    s
+}
+
+/// The user's preferred language according to their browser, e.g. "es-MX".
+pub fn browser_language() -> Option<String> {
+    #[cfg(feature = "web")]
+    {
+        web_sys::window().and_then(|w| w.navigator().language())
+    }
+    #[cfg(not(feature = "web"))]
+    {
+        None
+    }
+}
+
+/// Set the `lang` attribute of the document, e.g. "es".
+pub fn set_document_language(code: &str) {
+    #[cfg(feature = "web")]
+    if let Some(html) = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.document_element())
+    {
+        html.set_attribute("lang", code).ok();
+    }
+    #[cfg(not(feature = "web"))]
+    let _ = code;
+}
+
+/// Wait until the element is scrolled into view, or resolve right away if that can't be observed.
+pub async fn scrolled_into_view(element: &web_sys::Element) {
+    #[cfg(feature = "web")]
+    {
+        use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+        use web_sys::{
+            js_sys::{Array, Function, Promise},
+            IntersectionObserver, IntersectionObserverEntry,
+        };
+
+        let mut resolve: Option<Function> = None;
+        let visible = Promise::new(&mut |res, _| resolve = Some(res));
+        let resolve = resolve.expect("promise executors run immediately");
+        // called with the initial intersection and then on every change
+        let callback = Closure::<dyn FnMut(Array)>::new(move |entries: Array| {
+            let intersecting = entries.iter().any(|entry| {
+                entry
+                    .unchecked_into::<IntersectionObserverEntry>()
+                    .is_intersecting()
+            });
+            if intersecting {
+                resolve.call0(&JsValue::NULL).ok();
+            }
+        });
+        let Ok(observer) = IntersectionObserver::new(callback.as_ref().unchecked_ref()) else {
+            return;
+        };
+        observer.observe(element);
+        wasm_bindgen_futures::JsFuture::from(visible).await.ok();
+        observer.disconnect();
+    }
+    #[cfg(not(feature = "web"))]
+    let _ = element;
+}
+
+/// Leave the app for another site, e.g. a payment provider's checkout page.
+pub fn redirect(url: &str) {
+    #[cfg(feature = "web")]
+    if let Some(window) = web_sys::window() {
+        if let Err(e) = window.location().set_href(url) {
+            tracing::error!("Failed to redirect to {url}: {e:?}");
+        }
+    }
+    #[cfg(not(feature = "web"))]
+    let _ = url;
 }
@@ -0,0 +1,74 @@
+//! Licensing metadata for media, which must be complete before it's shipped to production.
+
+use std::env;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+/// Who to credit for a media file, and under which license it's used.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Attribution {
+    pub license: Option<String>,
+    pub author: Option<String>,
+    pub source_url: Option<String>,
+}
+
+impl Attribution {
+    /// Returns the names of any missing fields.
+    pub fn missing_fields(&self) -> Vec<&'static str> {
+        [
+            ("license", &self.license),
+            ("author", &self.author),
+            ("source_url", &self.source_url),
+        ]
+        .into_iter()
+        .filter(|(_, v)| v.as_deref().unwrap_or_default().is_empty())
+        .map(|(k, _)| k)
+        .collect()
+    }
+}
+
+/// Whether media must be fully attributed, i.e. when the `ENV` variable targets production.
+pub fn is_required() -> Result<bool> {
+    Ok(env::var("ENV")? == "production")
+}
+
+/// Fail unless every media file, given by its name, is fully attributed.
+pub fn validate<'a>(media: impl IntoIterator<Item = (&'a str, &'a Attribution)>) -> Result<()> {
+    let mut problems: Vec<String> = media
+        .into_iter()
+        .filter_map(|(name, attribution)| {
+            let missing = attribution.missing_fields();
+            (!missing.is_empty()).then(|| format!("{name}: missing {}", missing.join(", ")))
+        })
+        .collect();
+    if !problems.is_empty() {
+        problems.sort();
+        anyhow::bail!(
+            "refusing to ship unattributed media to production:\n  {}",
+            problems.join("\n  ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_unattributed_media() {
+        let attributed = Attribution {
+            license: Some("CC BY 4.0".to_string()),
+            author: Some("Jane Doe".to_string()),
+            source_url: Some("https://xeno-canto.org/1".to_string()),
+        };
+        let unattributed = Attribution {
+            author: Some(String::new()),
+            ..attributed.clone()
+        };
+        assert!(validate([("a.mp3", &attributed)]).is_ok());
+        let err = validate([("a.mp3", &attributed), ("b.mp3", &unattributed)]).unwrap_err();
+        assert!(err.to_string().contains("b.mp3: missing author"));
+    }
+}
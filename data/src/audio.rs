@@ -0,0 +1,48 @@
+//! Reading audio files, e.g. to find out how long recordings are.
+
+use std::{fs::File, path::Path};
+
+use anyhow::{Context, Result};
+use symphonia::core::{
+    formats::FormatOptions, io::MediaSourceStream, meta::MetadataOptions, probe::Hint,
+};
+
+/// How long the recording at `path` plays for, in milliseconds.
+pub fn duration_ms(path: &Path) -> Result<u32> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .with_context(|| format!("unsupported audio file {}", path.display()))?;
+    let params = probed
+        .format
+        .default_track()
+        .map(|track| &track.codec_params)
+        .with_context(|| format!("no audio in {}", path.display()))?;
+    match (params.n_frames, params.sample_rate) {
+        (Some(frames), Some(rate)) => Ok((frames * 1000 / u64::from(rate)) as u32),
+        _ => anyhow::bail!("unknown duration of {}", path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_durations_of_seed_recordings() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("seed/sounds/cardinalis-cardinalis/default.mp3");
+        // 474624 frames at 32kHz
+        assert_eq!(duration_ms(&path).unwrap(), 14832);
+    }
+}
@@ -0,0 +1,199 @@
+//! Import additional recordings per bird from a locally downloaded xeno-canto (JSON) or Macaulay
+//! Library (CSV) export. Nothing is fetched from the network, so the dump can be vetted before
+//! seeding; run with `--dry-run` to see what would be imported.
+//!
+//! Usage: import_recordings <metadata file> [options]
+//!
+//! Options:
+//!   --audio-dir <dir>     Directory holding the downloaded audio (default: next to metadata)
+//!   --min-quality <q>     Minimum quality, as a letter A-E or a number 0-5 (default: B)
+//!   --max-length <secs>   Skip recordings longer than this
+//!   --licenses <codes>    Comma separated creative commons codes to accept, e.g. by,by-sa, or ml
+//!                         for the Macaulay Library's own license
+//!   --max-per-bird <n>    Maximum number of recordings to import per bird (default: 5)
+//!   --dry-run             Don't touch storage or the database
+use std::{
+    collections::HashMap,
+    env,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use birdtalk_data::{
+    attribution, audio,
+    recordings::{self, Filters, Recording},
+    storage,
+};
+use sqlx::{Connection, PgConnection};
+
+#[derive(sqlx::FromRow)]
+struct BirdRow {
+    id: i32,
+    scientific_name: String,
+}
+
+struct Args {
+    metadata: PathBuf,
+    audio_dir: PathBuf,
+    filters: Filters,
+    max_per_bird: usize,
+    dry_run: bool,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut metadata = None;
+        let mut audio_dir = None;
+        let mut filters = Filters {
+            min_quality: 4,
+            ..Default::default()
+        };
+        let mut max_per_bird = 5;
+        let mut dry_run = false;
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("missing value for {arg}"))
+            };
+            match arg.as_str() {
+                "--audio-dir" => audio_dir = Some(PathBuf::from(value()?)),
+                "--min-quality" => {
+                    let value = value()?;
+                    filters.min_quality = recordings::parse_quality(&value)
+                        .with_context(|| format!("invalid quality: {value}"))?
+                }
+                "--max-length" => filters.max_length = Some(value()?.parse()?),
+                "--licenses" => {
+                    filters.licenses = value()?.split(',').map(str::to_uppercase).collect()
+                }
+                "--max-per-bird" => max_per_bird = value()?.parse()?,
+                "--dry-run" => dry_run = true,
+                flag if flag.starts_with("--") => anyhow::bail!("unknown option: {flag}"),
+                path => metadata = Some(PathBuf::from(path)),
+            }
+        }
+
+        let metadata = metadata.context("usage: import_recordings <metadata file> [options]")?;
+        let audio_dir = audio_dir
+            .unwrap_or_else(|| metadata.parent().map(Path::to_path_buf).unwrap_or_default());
+        Ok(Self {
+            metadata,
+            audio_dir,
+            filters,
+            max_per_bird,
+            dry_run,
+        })
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let args = Args::parse()?;
+
+    println!("Loading {}...", args.metadata.display());
+    let all = recordings::load(&args.metadata)?;
+    let total = all.len();
+    let accepted: Vec<Recording> = all
+        .into_iter()
+        .filter(|rec| args.filters.accepts(rec))
+        .collect();
+    println!(
+        "{} of {total} recordings pass the quality, length and license filters.",
+        accepted.len()
+    );
+    let mut by_species = recordings::select_best(accepted, args.max_per_bird);
+
+    // we can't ship media to production without knowing who to credit
+    if !args.dry_run && attribution::is_required()? {
+        attribution::validate(
+            by_species
+                .values()
+                .flatten()
+                .map(|rec| (rec.catalog_id.as_str(), &rec.attribution)),
+        )?;
+    }
+
+    let db_url = env::var("DATABASE_URL")?;
+    let mut conn = PgConnection::connect(&db_url).await?;
+    // dumps may use historical scientific names
+    let birds: HashMap<String, i32> = sqlx::query_as!(
+        BirdRow,
+        r#"select id as "id!", scientific_name as "scientific_name!" from birds
+        union all
+        select bird as "id!", scientific_name as "scientific_name!" from bird_aliases"#
+    )
+    .fetch_all(&mut conn)
+    .await?
+    .into_iter()
+    .map(|bird| (bird.scientific_name.to_lowercase(), bird.id))
+    .collect();
+
+    let mut unmatched: Vec<String> = by_species
+        .keys()
+        .filter(|name| !birds.contains_key(*name))
+        .cloned()
+        .collect();
+    unmatched.sort();
+    if !unmatched.is_empty() {
+        println!(
+            "Skipping species not in the birds table: {}",
+            unmatched.join(", ")
+        );
+    }
+
+    let mut imported = 0;
+    for (name, bird_id) in &birds {
+        let Some(recordings) = by_species.remove(name) else {
+            continue;
+        };
+        for rec in recordings {
+            let local_path = args.audio_dir.join(&rec.file_name);
+            if !local_path.exists() {
+                println!(
+                    "  missing audio for {}: {}",
+                    rec.catalog_id,
+                    local_path.display()
+                );
+                continue;
+            }
+            let path = rec.storage_path();
+            println!(
+                "  {path} ({}, quality {}, {})",
+                rec.type_.as_deref().unwrap_or("unknown type"),
+                rec.quality,
+                rec.attribution.license.as_deref().unwrap_or("unlicensed"),
+            );
+            if args.dry_run {
+                continue;
+            }
+
+            let duration_ms = audio::duration_ms(&local_path)? as i32;
+            storage::upload(&local_path.to_string_lossy(), "bird_sounds", &path)?;
+            sqlx::query!(
+                "insert into bird_sounds (bird, bucket, path, default_, type_, license, author, source_url, duration_ms)
+                values ($1, 'bird_sounds', $2, false, $3, $4, $5, $6, $7)
+                on conflict (bucket, path)
+                do update set type_ = $3, license = $4, author = $5, source_url = $6, duration_ms = $7",
+                bird_id,
+                path,
+                rec.type_,
+                rec.attribution.license,
+                rec.attribution.author,
+                rec.attribution.source_url,
+                duration_ms,
+            )
+            .execute(&mut conn)
+            .await?;
+            imported += 1;
+        }
+    }
+
+    if args.dry_run {
+        println!("\nDry run; nothing imported.");
+    } else {
+        println!("\nDone! Imported {imported} recordings.");
+    }
+    Ok(())
+}
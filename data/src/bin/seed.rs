@@ -2,10 +2,14 @@ use std::{
     collections::HashMap,
     env,
     io::{self, Write},
-    process::Command,
+    path::Path,
 };
 
 use anyhow::Result;
+use birdtalk_data::{
+    attribution::{self, Attribution},
+    audio, storage,
+};
 use sqlx::{Connection, PgConnection};
 
 const BLACKLIST: &[&str] = &[
@@ -28,30 +32,84 @@ struct BirdSeed {
     common_name: String,
     default_sound_file: String,
     img_file: String,
+    #[serde(default)]
+    sound_attribution: Attribution,
+    #[serde(default)]
+    img_attribution: Attribution,
+    family: Option<String>,
+    /// Popularity rank, where 0 is the most viewed bird
+    most_viewed_index: Option<u32>,
+    /// Region codes where the bird can be found, e.g. "us-east"
+    #[serde(default)]
+    regions: Vec<String>,
+}
+
+/// An entry of the taxonomy update file, which tracks checklist updates (e.g. eBird/Clements)
+/// such as splits and renames.
+#[derive(serde::Deserialize)]
+struct TaxonomyEntry {
+    species_code: String,
+    scientific_name: String,
+    #[serde(default)]
+    previous_scientific_names: Vec<String>,
+    /// Common names by locale, e.g. "en" or "es"
+    #[serde(default)]
+    common_names: HashMap<String, String>,
 }
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
     // pull seed data from filesystem
     let seed_dir = env::var("SEED_DIR")?;
+    let taxonomy: Vec<TaxonomyEntry> =
+        serde_json::from_reader(std::fs::File::open(format!("{seed_dir}/taxonomy.json"))?)?;
+    let renames: HashMap<&str, &str> = taxonomy
+        .iter()
+        .flat_map(|entry| {
+            entry
+                .previous_scientific_names
+                .iter()
+                .map(|prev| (prev.as_str(), entry.scientific_name.as_str()))
+        })
+        .collect();
     let file = std::fs::File::open(format!("{seed_dir}/birds.json"))?;
     let bird_seed: HashMap<String, BirdSeed> = serde_json::from_reader::<_, Vec<BirdSeed>>(file)?
         .into_iter()
-        .filter_map(|bird| {
+        .filter_map(|mut bird| {
             if BLACKLIST.contains(&bird.scientific_name.as_str()) {
                 None
             } else {
+                // seed data may predate the latest taxonomy
+                if let Some(name) = renames.get(bird.scientific_name.as_str()) {
+                    bird.scientific_name = name.to_string();
+                }
                 Some((bird.scientific_name.clone(), bird))
             }
         })
         .collect();
 
+    // we can't ship media to production without knowing who to credit
+    if attribution::is_required()? {
+        attribution::validate(bird_seed.values().flat_map(|bird| {
+            [
+                (bird.img_file.as_str(), &bird.img_attribution),
+                (bird.default_sound_file.as_str(), &bird.sound_attribution),
+            ]
+        }))?;
+    }
+
     // connect to db
     let db_url = env::var("DATABASE_URL")?;
     let mut conn = PgConnection::connect(&db_url).await?;
 
+    // rename existing birds before upserting, so that renamed species aren't duplicated
+    rename_birds(&taxonomy, &mut conn).await?;
     upsert_birds(&bird_seed, &mut conn).await?;
+    apply_taxonomy(&taxonomy, &mut conn).await?;
+    update_pack_metadata(&bird_seed, &mut conn).await?;
     upload_media(&bird_seed, &mut conn).await?;
+    update_attribution(&bird_seed, &mut conn).await?;
+    update_sound_durations(&bird_seed, &mut conn).await?;
     Ok(())
 }
 
@@ -81,6 +139,82 @@ async fn upsert_birds(
     Ok(())
 }
 
+async fn rename_birds(taxonomy: &[TaxonomyEntry], conn: &mut PgConnection) -> Result<()> {
+    println!("Renaming birds...");
+    let mut renamed = 0;
+    for entry in taxonomy {
+        renamed += sqlx::query!(
+            "update birds set scientific_name = $1
+            where (species_code = $2 or scientific_name = any($3))
+              and scientific_name <> $1
+              and not exists (select from birds b where b.scientific_name = $1)",
+            entry.scientific_name,
+            entry.species_code,
+            &entry.previous_scientific_names,
+        )
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+    println!("Done! Renamed {renamed} birds.");
+    Ok(())
+}
+
+async fn apply_taxonomy(taxonomy: &[TaxonomyEntry], conn: &mut PgConnection) -> Result<()> {
+    println!("Applying taxonomy...");
+    let mut updated = 0;
+    for entry in taxonomy {
+        let bird_id = sqlx::query_scalar!(
+            r#"select id as "id!" from birds where scientific_name = $1"#,
+            entry.scientific_name
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+        let Some(bird_id) = bird_id else {
+            continue;
+        };
+
+        sqlx::query!(
+            "update birds set species_code = $2, common_name = coalesce($3, common_name)
+            where id = $1",
+            bird_id,
+            entry.species_code,
+            entry.common_names.get("en").cloned(),
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query!(
+            "insert into bird_aliases (scientific_name, bird)
+            select unnest($1::text[]), $2
+            on conflict (scientific_name) do update set bird = excluded.bird",
+            &entry.previous_scientific_names,
+            bird_id,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        let (locales, names): (Vec<String>, Vec<String>) = entry
+            .common_names
+            .iter()
+            .map(|(locale, name)| (locale.clone(), name.clone()))
+            .unzip();
+        sqlx::query!(
+            "insert into bird_common_names (bird, locale, name)
+            select $1, * from unnest($2::text[], $3::text[])
+            on conflict (bird, locale) do update set name = excluded.name",
+            bird_id,
+            &locales,
+            &names,
+        )
+        .execute(&mut *conn)
+        .await?;
+        updated += 1;
+    }
+    println!("Done! Updated taxonomy of {updated} birds.");
+    Ok(())
+}
+
 async fn upload_media(
     bird_seed: &HashMap<String, BirdSeed>,
     conn: &mut PgConnection,
@@ -88,15 +222,16 @@ async fn upload_media(
     let seed_dir = env::var("SEED_DIR")?;
 
     println!("Upserting storage buckets...");
-    // first insert buckets if necessary
+    // first insert buckets if necessary, which are private so that only entitled users can sign
+    // URLs of premium packs' media
     sqlx::query!(
         r#"insert into storage.buckets
           (id, name, public)
         values
-          ('bird_images', 'bird_images', true),
-          ('bird_sounds', 'bird_sounds', true)
+          ('bird_images', 'bird_images', false),
+          ('bird_sounds', 'bird_sounds', false)
         on conflict (id)
-        do update set public = true"#
+        do update set public = false"#
     )
     .execute(&mut *conn)
     .await?;
@@ -116,14 +251,6 @@ async fn upload_media(
     .fetch_all(&mut *conn)
     .await?;
 
-    let sb_env = env::var("ENV")?;
-    let flag = match sb_env.as_str() {
-        "local" => "--local",
-        "staging" | "production" => "--linked",
-        _ => unimplemented!(),
-    };
-    let sb_args = vec!["--experimental", "storage", flag, "cp", "--recursive"];
-
     println!("Uploading media...");
     let total_birds = db_birds.len();
     for (ix, bird) in db_birds.into_iter().enumerate() {
@@ -134,21 +261,17 @@ async fn upload_media(
             // Upload media to supabase storage
             let seed_path = &bird_seed.default_sound_file;
             let local_path = format!("{seed_dir}/sounds/{seed_path}");
-            let remote_path = format!("ss:///bird_sounds/{seed_path}");
-            let mut storage_cmd = Command::new("supabase");
-            storage_cmd.args(&sb_args).arg(local_path).arg(remote_path);
-            let output = storage_cmd.output()?;
-            if !output.status.success() {
-                io::stdout().write_all(&output.stdout)?;
-                io::stderr().write_all(&output.stderr)?;
-                anyhow::bail!("failed to upload sound file");
-            }
+            storage::upload(&local_path, "bird_sounds", seed_path)?;
             // Add media link to database
+            let attribution = &bird_seed.sound_attribution;
             sqlx::query!(
-                "insert into bird_sounds (bird, bucket, path, default_)
-                values ($1, 'bird_sounds', $2, true)",
+                "insert into bird_sounds (bird, bucket, path, default_, license, author, source_url)
+                values ($1, 'bird_sounds', $2, true, $3, $4, $5)",
                 bird.id,
-                seed_path
+                seed_path,
+                attribution.license,
+                attribution.author,
+                attribution.source_url,
             )
             .execute(&mut *conn)
             .await?;
@@ -157,21 +280,17 @@ async fn upload_media(
             // Upload media to supabase storage
             let seed_path = &bird_seed.img_file;
             let local_path = format!("{seed_dir}/images/{seed_path}");
-            let remote_path = format!("ss:///bird_images/{seed_path}");
-            let mut storage_cmd = Command::new("supabase");
-            storage_cmd.args(&sb_args).arg(local_path).arg(remote_path);
-            let output = storage_cmd.output()?;
-            if !output.status.success() {
-                io::stdout().write_all(&output.stdout)?;
-                io::stderr().write_all(&output.stderr)?;
-                anyhow::bail!("failed to upload img file");
-            }
+            storage::upload(&local_path, "bird_images", seed_path)?;
             // Add media link to database
+            let attribution = &bird_seed.img_attribution;
             sqlx::query!(
-                "insert into bird_images (bird, bucket, path, default_)
-                values ($1, 'bird_images', $2, true)",
+                "insert into bird_images (bird, bucket, path, default_, license, author, source_url)
+                values ($1, 'bird_images', $2, true, $3, $4, $5)",
                 bird.id,
-                seed_path
+                seed_path,
+                attribution.license,
+                attribution.author,
+                attribution.source_url,
             )
             .execute(&mut *conn)
             .await?;
@@ -183,3 +302,101 @@ async fn upload_media(
     println!("\n\nDone!");
     Ok(())
 }
+
+/// Bring attribution of previously uploaded media up to date with the seed data.
+async fn update_attribution(
+    bird_seed: &HashMap<String, BirdSeed>,
+    conn: &mut PgConnection,
+) -> Result<()> {
+    println!("Updating media attribution...");
+    let mut updated = 0;
+    for bird in bird_seed.values() {
+        let attribution = &bird.img_attribution;
+        updated += sqlx::query!(
+            "update bird_images
+            set license = $2, author = $3, source_url = $4
+            where bucket = 'bird_images' and path = $1",
+            bird.img_file,
+            attribution.license,
+            attribution.author,
+            attribution.source_url,
+        )
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+        let attribution = &bird.sound_attribution;
+        updated += sqlx::query!(
+            "update bird_sounds
+            set license = $2, author = $3, source_url = $4
+            where bucket = 'bird_sounds' and path = $1",
+            bird.default_sound_file,
+            attribution.license,
+            attribution.author,
+            attribution.source_url,
+        )
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+    println!("Done! Updated {updated} media files.");
+    Ok(())
+}
+
+/// Record how long the default sounds play for, which the app server times answers against.
+async fn update_sound_durations(
+    bird_seed: &HashMap<String, BirdSeed>,
+    conn: &mut PgConnection,
+) -> Result<()> {
+    let seed_dir = env::var("SEED_DIR")?;
+    println!("Updating sound durations...");
+    let mut updated = 0;
+    for bird in bird_seed.values() {
+        let local_path = format!("{seed_dir}/sounds/{}", bird.default_sound_file);
+        let duration_ms = audio::duration_ms(Path::new(&local_path))? as i32;
+        updated += sqlx::query!(
+            "update bird_sounds
+            set duration_ms = $2
+            where bucket = 'bird_sounds' and path = $1",
+            bird.default_sound_file,
+            duration_ms,
+        )
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+    println!("Done! Updated durations of {updated} sounds.");
+    Ok(())
+}
+
+/// Set the family, difficulty and regions used to balance daily packs.
+async fn update_pack_metadata(
+    bird_seed: &HashMap<String, BirdSeed>,
+    conn: &mut PgConnection,
+) -> Result<()> {
+    println!("Updating daily pack metadata...");
+    // the most viewed birds are the most familiar, so the least viewed are the hardest
+    let mut by_views: Vec<&BirdSeed> = bird_seed.values().collect();
+    by_views.sort_by_key(|bird| {
+        (
+            bird.most_viewed_index.unwrap_or(u32::MAX),
+            &bird.scientific_name,
+        )
+    });
+    let max_rank = by_views.len().saturating_sub(1).max(1) as f32;
+    let mut updated = 0;
+    for (rank, bird) in by_views.into_iter().enumerate() {
+        updated += sqlx::query!(
+            "update birds set family = $2, difficulty = $3, regions = $4 where scientific_name = $1",
+            bird.scientific_name,
+            bird.family,
+            rank as f32 / max_rank,
+            &bird.regions,
+        )
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+    println!("Done! Updated {updated} birds.");
+    Ok(())
+}
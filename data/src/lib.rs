@@ -0,0 +1,6 @@
+//! Shared functionality for the data binaries.
+
+pub mod attribution;
+pub mod audio;
+pub mod recordings;
+pub mod storage;
@@ -0,0 +1,396 @@
+//! Recording metadata from locally downloaded xeno-canto (JSON) or Macaulay Library (CSV)
+//! exports, normalized so that they can be vetted and imported the same way.
+
+use std::{collections::HashMap, fs::File, path::Path};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::attribution::Attribution;
+
+/// The license of Macaulay Library media without a creative commons license, which may only be
+/// used under an agreement with the Cornell Lab of Ornithology.
+pub const MACAULAY_LICENSE: &str = "Macaulay Library Media License";
+
+/// A recording from a metadata dump.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    /// Catalog id prefixed by its source, e.g. "XC123456" or "ML123456"
+    pub catalog_id: String,
+    pub scientific_name: String,
+    /// The kind of vocalization, e.g. "song" or "call"
+    pub type_: Option<String>,
+    /// Quality rating normalized from 1 (worst) to 5 (best), or 0 if unrated
+    pub quality: u8,
+    /// Length in seconds
+    pub length: Option<u32>,
+    pub attribution: Attribution,
+    /// File name of the audio within the dump
+    pub file_name: String,
+}
+
+impl Recording {
+    /// Path within the `bird_sounds` bucket, alongside the seeded default sounds.
+    pub fn storage_path(&self) -> String {
+        let slug = self
+            .scientific_name
+            .to_lowercase()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("-");
+        let ext = Path::new(&self.file_name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("mp3");
+        format!("{slug}/{}.{ext}", self.catalog_id.to_lowercase())
+    }
+}
+
+/// Load recordings from a xeno-canto JSON export or a Macaulay Library CSV export.
+pub fn load(path: &Path) -> Result<Vec<Recording>> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => {
+            let dump: XenoCantoDump = serde_json::from_reader(file)?;
+            let recordings = match dump {
+                XenoCantoDump::Response { recordings } => recordings,
+                XenoCantoDump::Recordings(recordings) => recordings,
+            };
+            Ok(recordings.into_iter().map(Recording::from).collect())
+        }
+        Some("csv") => csv::Reader::from_reader(file)
+            .deserialize::<MacaulayRecording>()
+            .map(|row| Ok(Recording::from(row?)))
+            .collect(),
+        _ => anyhow::bail!(
+            "unsupported metadata file {}; expected .json or .csv",
+            path.display()
+        ),
+    }
+}
+
+/// Criteria a recording must meet to be imported.
+#[derive(Debug, Clone, Default)]
+pub struct Filters {
+    /// Minimum quality, from 0 to 5
+    pub min_quality: u8,
+    /// Maximum length in seconds
+    pub max_length: Option<u32>,
+    /// Accepted license codes, e.g. "BY" or "BY-NC-SA", or "ML" for the
+    /// [Macaulay Library's](MACAULAY_LICENSE). If empty, any license is accepted.
+    pub licenses: Vec<String>,
+}
+
+impl Filters {
+    pub fn accepts(&self, recording: &Recording) -> bool {
+        let quality_ok = recording.quality >= self.min_quality;
+        let length_ok = match (self.max_length, recording.length) {
+            (Some(max), Some(length)) => length <= max,
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let license_ok = self.licenses.is_empty()
+            || recording
+                .attribution
+                .license
+                .as_deref()
+                .and_then(license_code)
+                .filter(|code| self.licenses.iter().any(|l| l.eq_ignore_ascii_case(code)))
+                .is_some();
+        quality_ok && length_ok && license_ok
+    }
+}
+
+/// Group recordings by (lowercased) scientific name, keeping the best `max_per_bird` of each.
+pub fn select_best(
+    recordings: impl IntoIterator<Item = Recording>,
+    max_per_bird: usize,
+) -> HashMap<String, Vec<Recording>> {
+    let mut by_species: HashMap<String, Vec<Recording>> = HashMap::new();
+    for recording in recordings {
+        by_species
+            .entry(recording.scientific_name.to_lowercase())
+            .or_default()
+            .push(recording);
+    }
+    for recordings in by_species.values_mut() {
+        recordings.sort_by(|a, b| {
+            b.quality
+                .cmp(&a.quality)
+                .then_with(|| a.catalog_id.cmp(&b.catalog_id))
+        });
+        recordings.truncate(max_per_bird);
+    }
+    by_species
+}
+
+/// Parse a quality rating given either as a xeno-canto letter (A-E) or a number (0-5).
+pub fn parse_quality(s: &str) -> Option<u8> {
+    match s.trim().to_ascii_uppercase().as_str() {
+        "A" => Some(5),
+        "B" => Some(4),
+        "C" => Some(3),
+        "D" => Some(2),
+        "E" => Some(1),
+        n => n
+            .parse::<f32>()
+            .ok()
+            .filter(|q| (0.0..=5.0).contains(q))
+            .map(|q| q.round() as u8),
+    }
+}
+
+/// Parse lengths like "0:35" or "1:02:03" into seconds.
+fn parse_length(s: &str) -> Option<u32> {
+    s.trim().split(':').try_fold(0, |acc, part| {
+        part.parse::<u32>().ok().map(|n| acc * 60 + n)
+    })
+}
+
+/// Turn a creative commons URL like "//creativecommons.org/licenses/by-nc-sa/4.0/" into a
+/// readable license, e.g. "CC BY-NC-SA 4.0".
+fn cc_license_name(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("/licenses/")?;
+    let mut parts = rest.split('/').filter(|p| !p.is_empty());
+    let code = parts.next()?.to_uppercase();
+    match parts.next() {
+        Some(version) => Some(format!("CC {code} {version}")),
+        None => Some(format!("CC {code}")),
+    }
+}
+
+/// The code of a license, e.g. "BY-NC-SA" for "CC BY-NC-SA 4.0", or "ML" for the
+/// [Macaulay Library's](MACAULAY_LICENSE).
+fn license_code(license: &str) -> Option<&str> {
+    if license == MACAULAY_LICENSE {
+        return Some("ML");
+    }
+    license.strip_prefix("CC ")?.split_whitespace().next()
+}
+
+/// Normalize a license as given by a Macaulay Library export, which is either a creative commons
+/// license (as a URL or a name), or else Macaulay's own.
+fn macaulay_license(license: Option<String>) -> String {
+    match non_empty(license) {
+        Some(license) if license.contains("/licenses/") => {
+            cc_license_name(&license).unwrap_or(license)
+        }
+        Some(license) if license.to_uppercase().starts_with("CC ") => license.to_uppercase(),
+        _ => MACAULAY_LICENSE.to_string(),
+    }
+}
+
+fn non_empty(s: Option<String>) -> Option<String> {
+    s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+/// Either a raw API response or just its list of recordings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum XenoCantoDump {
+    Response { recordings: Vec<XenoCantoRecording> },
+    Recordings(Vec<XenoCantoRecording>),
+}
+
+#[derive(Deserialize)]
+struct XenoCantoRecording {
+    id: String,
+    gen: String,
+    sp: String,
+    #[serde(default)]
+    r#type: Option<String>,
+    #[serde(default)]
+    rec: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(rename = "file-name")]
+    file_name: String,
+    #[serde(default)]
+    lic: Option<String>,
+    #[serde(default)]
+    q: Option<String>,
+    #[serde(default)]
+    length: Option<String>,
+}
+
+impl From<XenoCantoRecording> for Recording {
+    fn from(rec: XenoCantoRecording) -> Self {
+        Self {
+            catalog_id: format!("XC{}", rec.id),
+            scientific_name: format!("{} {}", rec.gen, rec.sp),
+            type_: non_empty(rec.r#type).map(|t| t.to_lowercase()),
+            quality: rec.q.as_deref().and_then(parse_quality).unwrap_or(0),
+            length: rec.length.as_deref().and_then(parse_length),
+            attribution: Attribution {
+                license: rec.lic.as_deref().and_then(cc_license_name),
+                author: non_empty(rec.rec),
+                source_url: non_empty(rec.url).map(|url| match url.strip_prefix("//") {
+                    Some(url) => format!("https://{url}"),
+                    None => url,
+                }),
+            },
+            file_name: rec.file_name,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct MacaulayRecording {
+    #[serde(rename = "ML Catalog Number")]
+    catalog_number: String,
+    #[serde(rename = "Scientific Name")]
+    scientific_name: String,
+    #[serde(rename = "Recordist", default)]
+    recordist: Option<String>,
+    #[serde(rename = "Average Community Rating", default)]
+    rating: Option<String>,
+    #[serde(rename = "Behaviors", default)]
+    behaviors: Option<String>,
+    #[serde(rename = "Duration", default)]
+    duration: Option<String>,
+    #[serde(rename = "License", default)]
+    license: Option<String>,
+}
+
+impl From<MacaulayRecording> for Recording {
+    fn from(rec: MacaulayRecording) -> Self {
+        let catalog_number = rec.catalog_number.trim().to_string();
+        Self {
+            catalog_id: format!("ML{catalog_number}"),
+            scientific_name: rec.scientific_name.trim().to_string(),
+            type_: non_empty(rec.behaviors).map(|t| t.to_lowercase()),
+            quality: rec.rating.as_deref().and_then(parse_quality).unwrap_or(0),
+            length: rec.duration.as_deref().and_then(parse_length),
+            attribution: Attribution {
+                license: Some(macaulay_license(rec.license)),
+                author: non_empty(rec.recordist),
+                source_url: Some(format!(
+                    "https://macaulaylibrary.org/asset/{catalog_number}"
+                )),
+            },
+            file_name: format!("{catalog_number}.mp3"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording() -> Recording {
+        Recording {
+            catalog_id: "XC1".to_string(),
+            scientific_name: "Strix varia".to_string(),
+            type_: Some("call".to_string()),
+            quality: 4,
+            length: Some(30),
+            attribution: Attribution {
+                license: Some("CC BY-NC-SA 4.0".to_string()),
+                author: Some("Jane Doe".to_string()),
+                source_url: None,
+            },
+            file_name: "XC1-Barred-Owl.mp3".to_string(),
+        }
+    }
+
+    #[test]
+    fn normalizes_xeno_canto_metadata() {
+        let json = r#"{"recordings": [{
+            "id": "123456", "gen": "Strix", "sp": "varia", "en": "Barred Owl",
+            "rec": "Jane Doe", "type": "Song", "url": "//xeno-canto.org/123456",
+            "file-name": "XC123456-Barred-Owl.mp3",
+            "lic": "//creativecommons.org/licenses/by-nc-sa/4.0/",
+            "q": "A", "length": "1:05"
+        }]}"#;
+        let XenoCantoDump::Response { recordings } = serde_json::from_str(json).unwrap() else {
+            panic!("expected an API response");
+        };
+        let recording = Recording::from(recordings.into_iter().next().unwrap());
+        assert_eq!(recording.catalog_id, "XC123456");
+        assert_eq!(recording.scientific_name, "Strix varia");
+        assert_eq!(recording.type_.as_deref(), Some("song"));
+        assert_eq!(recording.quality, 5);
+        assert_eq!(recording.length, Some(65));
+        assert_eq!(
+            recording.attribution.license.as_deref(),
+            Some("CC BY-NC-SA 4.0")
+        );
+        assert_eq!(
+            recording.attribution.source_url.as_deref(),
+            Some("https://xeno-canto.org/123456")
+        );
+        assert_eq!(recording.storage_path(), "strix-varia/xc123456.mp3");
+    }
+
+    #[test]
+    fn filters_by_quality_length_and_license() {
+        let filters = Filters {
+            min_quality: 4,
+            max_length: Some(60),
+            licenses: vec!["by-nc-sa".to_string()],
+        };
+        assert!(filters.accepts(&recording()));
+
+        assert!(!filters.accepts(&Recording {
+            quality: 3,
+            ..recording()
+        }));
+        assert!(!filters.accepts(&Recording {
+            length: Some(61),
+            ..recording()
+        }));
+        assert!(!filters.accepts(&Recording {
+            attribution: Attribution {
+                license: Some("CC BY-ND 4.0".to_string()),
+                ..recording().attribution
+            },
+            ..recording()
+        }));
+    }
+
+    #[test]
+    fn normalizes_macaulay_metadata() {
+        let csv = "ML Catalog Number,Scientific Name,Recordist,Average Community Rating,Duration,License\n\
+                   123,Strix varia,Jane Doe,4.2,0:35,\n\
+                   456,Strix varia,John Doe,3,1:00,https://creativecommons.org/licenses/by-nc/4.0/\n";
+        let recordings: Vec<Recording> = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize::<MacaulayRecording>()
+            .map(|row| Recording::from(row.unwrap()))
+            .collect();
+        assert_eq!(recordings[0].catalog_id, "ML123");
+        assert_eq!(recordings[0].quality, 4);
+        assert_eq!(recordings[0].length, Some(35));
+        assert_eq!(
+            recordings[0].attribution.license.as_deref(),
+            Some(MACAULAY_LICENSE)
+        );
+        assert_eq!(
+            recordings[1].attribution.license.as_deref(),
+            Some("CC BY-NC 4.0")
+        );
+        assert!(recordings[0].attribution.missing_fields().is_empty());
+
+        let filters = |license: &str| Filters {
+            licenses: vec![license.to_string()],
+            ..Default::default()
+        };
+        assert!(filters("ML").accepts(&recordings[0]));
+        assert!(!filters("ML").accepts(&recordings[1]));
+        assert!(filters("BY-NC").accepts(&recordings[1]));
+    }
+
+    #[test]
+    fn selects_best_recordings_per_species() {
+        let recordings = (1..=4).map(|q| Recording {
+            catalog_id: format!("XC{q}"),
+            quality: q,
+            ..recording()
+        });
+        let selected = select_best(recordings, 2);
+        let ids: Vec<_> = selected["strix varia"]
+            .iter()
+            .map(|r| r.catalog_id.as_str())
+            .collect();
+        assert_eq!(ids, ["XC4", "XC3"]);
+    }
+}
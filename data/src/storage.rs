@@ -0,0 +1,29 @@
+use std::{
+    env,
+    io::{self, Write},
+    process::Command,
+};
+
+use anyhow::Result;
+
+/// Upload a local file to Supabase storage via the supabase CLI.
+///
+/// Respects the `ENV` variable to target either the local or linked project.
+pub fn upload(local_path: &str, bucket: &str, remote_path: &str) -> Result<()> {
+    let flag = match env::var("ENV")?.as_str() {
+        "local" => "--local",
+        "staging" | "production" => "--linked",
+        env => anyhow::bail!("unknown ENV {env:?}; expected local, staging or production"),
+    };
+    let output = Command::new("supabase")
+        .args(["--experimental", "storage", flag, "cp", "--recursive"])
+        .arg(local_path)
+        .arg(format!("ss:///{bucket}/{remote_path}"))
+        .output()?;
+    if !output.status.success() {
+        io::stdout().write_all(&output.stdout)?;
+        io::stderr().write_all(&output.stderr)?;
+        anyhow::bail!("failed to upload {local_path}");
+    }
+    Ok(())
+}
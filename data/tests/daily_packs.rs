@@ -0,0 +1,188 @@
+//! Properties of the daily pack generation in `create_daily_pack`, checked against a local
+//! database (e.g. `just supabase-up`) over a simulated year.
+//!
+//! Each test runs in a transaction that is rolled back, using fixture birds in their own region
+//! and days far in the future, so the database is left untouched. Run with `just test-db`.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use sqlx::{PgPool, Postgres, Transaction};
+
+const REGION: &str = "test-region";
+const START: &str = "2100-01-01";
+const PACK_SIZE: i32 = 10;
+const LOOKBACK_DAYS: i32 = 7;
+const NUM_BIRDS: i32 = 120;
+const NUM_FAMILIES: i32 = 12;
+
+#[derive(sqlx::FromRow)]
+struct FixtureBird {
+    id: i32,
+    family: String,
+    difficulty: f32,
+    regions: Vec<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct PackBird {
+    day: i32,
+    bird: i32,
+}
+
+async fn setup() -> Result<(Transaction<'static, Postgres>, Vec<FixtureBird>)> {
+    let pool = PgPool::connect(&std::env::var("DATABASE_URL")?).await?;
+    let mut tx = pool.begin().await?;
+    // every other bird is also found in a second region
+    let birds = sqlx::query_as(
+        "insert into birds (scientific_name, common_name, family, difficulty, regions)
+        select
+            'Testus birdus ' || i,
+            'Test Bird ' || i,
+            'Test family ' || i % $2,
+            (i * 37 % $1) / ($1 - 1)::real,
+            case when i % 2 = 0 then array[$3, $3 || '-b'] else array[$3] end
+        from generate_series(1, $1) i
+        returning id, family, difficulty, regions",
+    )
+    .bind(NUM_BIRDS)
+    .bind(NUM_FAMILIES)
+    .bind(REGION)
+    .fetch_all(&mut *tx)
+    .await?;
+    Ok((tx, birds))
+}
+
+async fn create_pack(
+    tx: &mut Transaction<'static, Postgres>,
+    day_index: i32,
+    region: &str,
+) -> Result<()> {
+    sqlx::query("select create_daily_pack($1::date + $2, $3, $4, $5)")
+        .bind(START)
+        .bind(day_index)
+        .bind(region)
+        .bind(PACK_SIZE)
+        .bind(LOOKBACK_DAYS)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Birds of each generated pack, by days since [`START`].
+async fn packs(tx: &mut Transaction<'static, Postgres>) -> Result<Vec<Vec<i32>>> {
+    let rows: Vec<PackBird> = sqlx::query_as(
+        "select dp.day - $1::date as day, bp.bird
+        from daily_packs dp
+        join bird_pack bp on bp.pack = dp.pack
+        where dp.day >= $1::date
+        order by dp.day, bp.bird",
+    )
+    .bind(START)
+    .fetch_all(&mut **tx)
+    .await?;
+    let mut packs = Vec::new();
+    for row in rows {
+        let day = row.day as usize;
+        packs.resize_with(packs.len().max(day + 1), Vec::new);
+        packs[day].push(row.bird);
+    }
+    Ok(packs)
+}
+
+#[tokio::test]
+#[ignore = "requires a local database"]
+async fn balances_packs_without_repeats_over_a_year() -> Result<()> {
+    let (mut tx, birds) = setup().await?;
+    for day in 0..365 {
+        create_pack(&mut tx, day, REGION).await?;
+    }
+    let packs = packs(&mut tx).await?;
+    assert_eq!(packs.len(), 365);
+
+    let families: HashMap<i32, &str> = birds.iter().map(|b| (b.id, b.family.as_str())).collect();
+    // easy, medium and hard thirds of the birds, as split by the generator
+    let mut by_difficulty: Vec<&FixtureBird> = birds.iter().collect();
+    by_difficulty.sort_by(|a, b| a.difficulty.total_cmp(&b.difficulty).then(a.id.cmp(&b.id)));
+    let tiers: HashMap<i32, usize> = by_difficulty
+        .iter()
+        .enumerate()
+        .map(|(ix, b)| (b.id, ix * 3 / birds.len()))
+        .collect();
+
+    let mut last_used: HashMap<i32, usize> = HashMap::new();
+    for (day, pack) in packs.iter().enumerate() {
+        assert_eq!(pack.len(), PACK_SIZE as usize, "day {day}");
+
+        for bird in pack {
+            if let Some(prev) = last_used.insert(*bird, day) {
+                assert!(
+                    day - prev > LOOKBACK_DAYS as usize,
+                    "bird {bird} repeated on days {prev} and {day}"
+                );
+            }
+        }
+
+        let distinct_families: HashSet<&str> = pack.iter().map(|b| families[b]).collect();
+        assert!(
+            distinct_families.len() >= 8,
+            "day {day} only has {} families",
+            distinct_families.len()
+        );
+
+        let mut tier_counts = [0; 3];
+        for bird in pack {
+            tier_counts[tiers[bird]] += 1;
+        }
+        assert!(
+            tier_counts.iter().all(|&n| n >= 3),
+            "day {day} is unbalanced: {tier_counts:?}"
+        );
+    }
+
+    // every bird gets its turn
+    assert_eq!(last_used.len(), birds.len());
+    tx.rollback().await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires a local database"]
+async fn restricts_packs_to_region() -> Result<()> {
+    let (mut tx, birds) = setup().await?;
+    let region = format!("{REGION}-b");
+    for day in 0..30 {
+        create_pack(&mut tx, day, &region).await?;
+    }
+    let in_region: HashSet<i32> = birds
+        .iter()
+        .filter(|b| b.regions.contains(&region))
+        .map(|b| b.id)
+        .collect();
+    for (day, pack) in packs(&mut tx).await?.iter().enumerate() {
+        assert_eq!(pack.len(), PACK_SIZE as usize, "day {day}");
+        assert!(pack.iter().all(|b| in_region.contains(b)), "day {day}");
+    }
+    tx.rollback().await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires a local database"]
+async fn is_deterministic_per_day() -> Result<()> {
+    let (mut tx, _) = setup().await?;
+    create_pack(&mut tx, 0, REGION).await?;
+    let first = packs(&mut tx).await?;
+
+    sqlx::query(
+        "with deleted as (delete from daily_packs where day = $1::date returning pack)
+        delete from packs where id in (select pack from deleted)",
+    )
+    .bind(START)
+    .execute(&mut *tx)
+    .await?;
+    create_pack(&mut tx, 0, REGION).await?;
+    assert_eq!(packs(&mut tx).await?, first);
+    tx.rollback().await?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+-- Birds can now have multiple recordings, e.g. both songs and calls
+alter table bird_sounds
+  add column type_ text;
+
+-- drop dependent views so that we can recreate the sound type
+drop view bird_packs_detailed;
+drop view birds_detailed;
+drop type sound;
+
+create type sound as (
+  path text,
+  default_ bool,
+  type_ text,
+  attribution attribution
+);
+
+-- NOTE: the default sound is ordered first
+create view birds_detailed
+  with (security_invoker=on)
+  as
+  select
+      b.id,
+      b.scientific_name,
+      b.common_name,
+      bi.bucket || '/' || bi.path as image,
+      (bi.license, bi.author, bi.source_url)::attribution as image_attribution,
+      array_agg((
+        bs.bucket || '/' || bs.path,
+        bs.default_,
+        bs.type_,
+        (bs.license, bs.author, bs.source_url)::attribution
+      )::sound order by bs.default_ desc, bs.path) as sounds
+  from birds b
+  left join bird_images bi on bi.bird = b.id and bi.default_ = true
+  left join bird_sounds bs on bs.bird = b.id
+  group by b.id, bi.bucket, bi.path, bi.license, bi.author, bi.source_url;
+
+create view bird_packs_detailed
+  with (security_invoker=on)
+  as
+  select
+      p.id,
+      p.name,
+      p.description,
+      array_agg(b.*) as birds,
+      dp.day
+  from packs p
+  left join bird_pack bp on p.id = bp.pack
+  left join birds_detailed b on b.id = bp.bird
+  left join daily_packs dp on dp.pack = p.id
+  group by p.id, dp.day;
@@ -0,0 +1,86 @@
+-- Stable species codes (eBird/Clements), e.g. "norcar" for the Northern Cardinal. Scientific
+-- names change with taxonomic splits and renames, but these codes don't.
+alter table birds add column species_code text;
+create unique index bird_species_code on public.birds using btree (species_code);
+
+-- Historical scientific names, so that older data can still be matched to birds
+create table bird_aliases (
+  scientific_name text primary key,
+  bird integer not null,
+  constraint fk_bird_aliases_bird
+    foreign key (bird) references birds(id)
+    on delete cascade
+);
+create index idx_bird_aliases_bird on public.bird_aliases using btree (bird);
+
+-- Localized common names
+create table bird_common_names (
+  bird integer not null,
+  locale text not null,
+  name text not null,
+  constraint fk_bird_common_names_bird
+    foreign key (bird) references birds(id)
+    on delete cascade,
+  constraint bird_common_names_pkey primary key (bird, locale)
+);
+
+alter table "bird_aliases" enable row level security;
+alter table "bird_common_names" enable row level security;
+
+create policy "Enable read access for all users"
+on "public"."bird_aliases"
+as PERMISSIVE
+for SELECT
+to public
+using (true);
+
+create policy "Enable read access for all users"
+on "public"."bird_common_names"
+as PERMISSIVE
+for SELECT
+to public
+using (true);
+
+-- recreate views with species codes and localized names
+drop view bird_packs_detailed;
+drop view birds_detailed;
+
+create view birds_detailed
+  with (security_invoker=on)
+  as
+  select
+      b.id,
+      b.species_code,
+      b.scientific_name,
+      b.common_name,
+      coalesce(
+        (select jsonb_object_agg(n.locale, n.name) from bird_common_names n where n.bird = b.id),
+        '{}'::jsonb
+      ) as common_names,
+      bi.bucket || '/' || bi.path as image,
+      (bi.license, bi.author, bi.source_url)::attribution as image_attribution,
+      array_agg((
+        bs.bucket || '/' || bs.path,
+        bs.default_,
+        bs.type_,
+        (bs.license, bs.author, bs.source_url)::attribution
+      )::sound order by bs.default_ desc, bs.path) as sounds
+  from birds b
+  left join bird_images bi on bi.bird = b.id and bi.default_ = true
+  left join bird_sounds bs on bs.bird = b.id
+  group by b.id, bi.bucket, bi.path, bi.license, bi.author, bi.source_url;
+
+create view bird_packs_detailed
+  with (security_invoker=on)
+  as
+  select
+      p.id,
+      p.name,
+      p.description,
+      array_agg(b.*) as birds,
+      dp.day
+  from packs p
+  left join bird_pack bp on p.id = bp.pack
+  left join birds_detailed b on b.id = bp.bird
+  left join daily_packs dp on dp.pack = p.id
+  group by p.id, dp.day;
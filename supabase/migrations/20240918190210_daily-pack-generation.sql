@@ -0,0 +1,96 @@
+-- Metadata used to build balanced daily packs
+alter table birds
+  add column family text,
+  -- 0 (easiest) to 1 (hardest); seeded from how commonly viewed the bird is
+  add column difficulty real,
+  -- region codes where the bird can be found, e.g. "us-east"
+  add column regions text[] not null default '{}';
+
+create index idx_birds_regions on public.birds using gin (regions);
+
+-- Generate the daily pack for a given day. Birds are picked one at a time, each pick:
+--   * avoiding birds used in the daily packs of the previous `lookback_days` (falling back to the
+--     least recently used birds if there aren't enough),
+--   * cycling through easy, medium and hard birds,
+--   * preferring families not yet in the pack,
+--   * optionally restricted to birds found in `region`.
+-- The selection is deterministic per day.
+create or replace function create_daily_pack(
+  day date,
+  region text default null,
+  pack_size integer default 10,
+  lookback_days integer default 14
+)
+returns bigint
+language plpgsql
+set search_path = ''
+as $$
+declare
+  new_pack_id bigint;
+  chosen integer[] := '{}';
+  chosen_families text[] := '{}';
+  next_bird record;
+begin
+  insert into public.packs(name, description, free)
+  values (
+    'Daily Bevy for ' || create_daily_pack.day,
+    'The daily challenge for ' || to_char(create_daily_pack.day, 'FMDay, FMMonth FMDDth YYYY'),
+    true
+  )
+  returning id into new_pack_id;
+
+  insert into public.daily_packs(pack, day)
+  values (new_pack_id, create_daily_pack.day);
+
+  for i in 0 .. create_daily_pack.pack_size - 1 loop
+    select c.id, c.family into next_bird
+    from (
+      select
+        b.id,
+        coalesce(b.family, b.id::text) as family,
+        ntile(3) over (order by coalesce(b.difficulty, 0.5), b.id) as tier,
+        -- null if not used within the lookback window
+        (
+          select max(dp.day)
+          from public.daily_packs dp
+          join public.bird_pack bp on bp.pack = dp.pack
+          where bp.bird = b.id
+            and dp.day < create_daily_pack.day
+            and dp.day >= create_daily_pack.day - create_daily_pack.lookback_days
+        ) as last_used
+      from public.birds b
+      where create_daily_pack.region is null or create_daily_pack.region = any(b.regions)
+    ) c
+    where not c.id = any(chosen)
+    order by
+      c.last_used nulls first,
+      c.tier = i % 3 + 1 desc,
+      c.family = any(chosen_families),
+      -- shuffle, deterministically per day
+      md5(c.id || '/' || create_daily_pack.day)
+    limit 1;
+
+    exit when not found;
+    chosen := chosen || next_bird.id;
+    chosen_families := chosen_families || next_bird.family;
+  end loop;
+
+  insert into public.bird_pack(bird, pack)
+  select unnest(chosen), new_pack_id;
+
+  return new_pack_id;
+end;
+$$;
+
+drop function create_random_daily_pack(date);
+
+-- replaces the existing job of the same name
+select cron.schedule(
+  'next-pack-of-the-day',
+  '1 0 * * *', -- everyday, just after midnight
+  $$
+  -- create tomorrow's pack if it doesn't exist yet
+  select create_daily_pack(current_date + 1)
+  where not exists (select from daily_packs where day = current_date + 1);
+  $$
+);
@@ -0,0 +1,24 @@
+-- Expose whether packs are free, so that the app can sign the URLs of other packs' media.
+create or replace view bird_packs_detailed
+  with (security_invoker=on)
+  as
+  select
+      p.id,
+      p.name,
+      p.description,
+      array_agg(b.*) as birds,
+      dp.day,
+      p.free
+  from packs p
+  left join bird_pack bp on p.id = bp.pack
+  left join birds_detailed b on b.id = bp.bird
+  left join daily_packs dp on dp.pack = p.id
+  group by p.id, dp.day;
+
+-- Signing URLs requires read access to the objects.
+create policy "Enable read access to bird media for all users"
+on "storage"."objects"
+as PERMISSIVE
+for SELECT
+to public
+using (bucket_id in ('bird_images', 'bird_sounds'));
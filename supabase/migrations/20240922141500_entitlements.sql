@@ -0,0 +1,79 @@
+-- Packs that users have paid for. Rows are only written by the payment provider's webhook (with
+-- the service role), so users can only read their own.
+create table public.entitlements (
+  id integer primary key generated always as identity,
+  user_id uuid not null,
+  -- null for entitlements to every pack, e.g. a subscription
+  pack integer,
+  -- which payment provider granted it, and its id for the purchase
+  provider text not null,
+  reference text not null,
+  created_at timestamp with time zone not null default (now() at time zone 'utc'),
+  expires_at timestamp with time zone,
+  constraint fk_entitlements_users_user_id
+    foreign key (user_id) references auth.users(id)
+    on delete cascade,
+  constraint fk_entitlements_packs_pack
+    foreign key (pack) references packs(id)
+    on delete cascade,
+  constraint entitlements_purchase unique (provider, reference)
+);
+
+create index idx_entitlements_user on public.entitlements using btree (user_id);
+
+alter table "entitlements" enable row level security;
+create policy "Enable read access based on user_id"
+on "public"."entitlements"
+as PERMISSIVE
+for SELECT
+to authenticated
+using ( (select auth.uid()) = user_id );
+
+-- Whether the current user may play a pack: anyone may play free packs (e.g. daily packs), and
+-- entitled users the others.
+create or replace function is_entitled(pack integer)
+returns boolean
+language sql
+stable
+security definer
+set search_path = ''
+as $$
+  select exists (
+    select 1 from public.packs p where p.id = is_entitled.pack and p.free
+  ) or exists (
+    select 1 from public.entitlements e
+    where e.user_id = (select auth.uid())
+      and (e.pack is null or e.pack = is_entitled.pack)
+      and (e.expires_at is null or e.expires_at > now())
+  );
+$$;
+
+-- Bird media is only served with signed URLs from now on, since objects in public buckets are
+-- served without checking policies. The seeder creates the buckets private too.
+update storage.buckets set public = false where id in ('bird_images', 'bird_sounds');
+
+-- Only entitled users can sign URLs of bird media, i.e. media of birds in a pack they may play.
+-- A bird in any free pack may be played by anyone, so its media is served to everyone even if
+-- it's also in premium packs. Daily packs are free and drawn from every bird, so this only keeps
+-- premium birds private until they first come up in a daily pack.
+drop policy "Enable read access to bird media for all users" on "storage"."objects";
+create policy "Enable read access to bird media for entitled users"
+on "storage"."objects"
+as PERMISSIVE
+for SELECT
+to public
+using (
+  bucket_id in ('bird_images', 'bird_sounds')
+  and exists (
+    select 1
+    from (
+      select bi.bird from public.bird_images bi
+      where bi.bucket = objects.bucket_id and bi.path = objects.name
+      union all
+      select bs.bird from public.bird_sounds bs
+      where bs.bucket = objects.bucket_id and bs.path = objects.name
+    ) media
+    join public.bird_pack bp on bp.bird = media.bird
+    where public.is_entitled(bp.pack)
+  )
+);
@@ -0,0 +1,40 @@
+-- Stats recorded by the app server from the games users finish (see app/src/server), unlike
+-- `stats`, which users sync from their browser and so can write whatever they like. Rows are only
+-- written with the service role, so users can only read their own.
+create table public.verified_stats (
+  user_id uuid primary key,
+  data jsonb not null default '{}',
+  updated_at timestamp with time zone not null default (now() at time zone 'utc'),
+  constraint fk_verified_stats_users_user_id
+    foreign key (user_id) references auth.users(id)
+    on delete cascade
+);
+
+alter table "verified_stats" enable row level security;
+create policy "Enable read access based on user_id"
+on "public"."verified_stats"
+as PERMISSIVE
+for SELECT
+to authenticated
+using ( (select auth.uid()) = user_id );
+
+-- Everyone's XP, as awarded by the app server along with their verified stats.
+create table public.leaderboard (
+  user_id uuid primary key,
+  xp integer not null default 0,
+  level integer not null default 1,
+  updated_at timestamp with time zone not null default (now() at time zone 'utc'),
+  constraint fk_leaderboard_users_user_id
+    foreign key (user_id) references auth.users(id)
+    on delete cascade
+);
+
+create index idx_leaderboard_xp on public.leaderboard using btree (xp desc);
+
+alter table "leaderboard" enable row level security;
+create policy "Enable read access for all users"
+on "public"."leaderboard"
+as PERMISSIVE
+for SELECT
+to public
+using (true);
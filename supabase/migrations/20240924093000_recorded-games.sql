@@ -0,0 +1,28 @@
+-- Games the app server has validated and applied to `verified_stats` (see app/src/server), with
+-- every answer, so that stats can be derived again, e.g. when scoring changes. Every session the
+-- server signs has a random nonce, so the user and nonce identify a game, and stop it from being
+-- recorded twice.
+create table public.recorded_games (
+  user_id uuid not null,
+  -- an unsigned 64 bit integer, which is too big for a bigint
+  nonce numeric(20, 0) not null,
+  signature text not null,
+  -- as in the app's URLs, e.g. a pack id, a date for a daily pack or a list of bird ids
+  pack text not null,
+  started_at timestamp with time zone not null,
+  answers jsonb not null,
+  recorded_at timestamp with time zone not null default (now() at time zone 'utc'),
+  primary key (user_id, nonce),
+  constraint fk_recorded_games_users_user_id
+    foreign key (user_id) references auth.users(id)
+    on delete cascade
+);
+
+-- Only written with the service role
+alter table "recorded_games" enable row level security;
+create policy "Enable read access based on user_id"
+on "public"."recorded_games"
+as PERMISSIVE
+for SELECT
+to authenticated
+using ( (select auth.uid()) = user_id );
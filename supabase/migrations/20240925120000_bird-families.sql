@@ -0,0 +1,27 @@
+-- Expose bird families, so that the app can chart progress per family. New columns have to go
+-- last to replace the view in place.
+create or replace view birds_detailed
+  with (security_invoker=on)
+  as
+  select
+      b.id,
+      b.species_code,
+      b.scientific_name,
+      b.common_name,
+      coalesce(
+        (select jsonb_object_agg(n.locale, n.name) from bird_common_names n where n.bird = b.id),
+        '{}'::jsonb
+      ) as common_names,
+      bi.bucket || '/' || bi.path as image,
+      (bi.license, bi.author, bi.source_url)::attribution as image_attribution,
+      array_agg((
+        bs.bucket || '/' || bs.path,
+        bs.default_,
+        bs.type_,
+        (bs.license, bs.author, bs.source_url)::attribution
+      )::sound order by bs.default_ desc, bs.path) as sounds,
+      b.family
+  from birds b
+  left join bird_images bi on bi.bird = b.id and bi.default_ = true
+  left join bird_sounds bs on bs.bird = b.id
+  group by b.id, bi.bucket, bi.path, bi.license, bi.author, bi.source_url;
@@ -0,0 +1,35 @@
+-- How long recordings play for, so that the app server can tell answers that came in before a
+-- recording could have been heard (see app/src/server). New attributes and columns have to go last
+-- to replace the view in place.
+alter table bird_sounds
+  add column duration_ms integer;
+
+alter type sound
+  add attribute duration_ms integer;
+
+create or replace view birds_detailed
+  with (security_invoker=on)
+  as
+  select
+      b.id,
+      b.species_code,
+      b.scientific_name,
+      b.common_name,
+      coalesce(
+        (select jsonb_object_agg(n.locale, n.name) from bird_common_names n where n.bird = b.id),
+        '{}'::jsonb
+      ) as common_names,
+      bi.bucket || '/' || bi.path as image,
+      (bi.license, bi.author, bi.source_url)::attribution as image_attribution,
+      array_agg((
+        bs.bucket || '/' || bs.path,
+        bs.default_,
+        bs.type_,
+        (bs.license, bs.author, bs.source_url)::attribution,
+        bs.duration_ms
+      )::sound order by bs.default_ desc, bs.path) as sounds,
+      b.family
+  from birds b
+  left join bird_images bi on bi.bird = b.id and bi.default_ = true
+  left join bird_sounds bs on bs.bird = b.id
+  group by b.id, bi.bucket, bi.path, bi.license, bi.author, bi.source_url;
@@ -0,0 +1,63 @@
+-- Verified stats are read, applied a game to and written back by the app server, so each write
+-- bumps their version, and is only made if they're still at the version that was read. Otherwise
+-- two games recorded at once could both apply to the same stats, and the first would be lost.
+alter table public.verified_stats add column version bigint not null default 0;
+
+-- Record a game the app server has validated (see app/src/server), along with the verified stats
+-- it was applied to and the user's standing on the leaderboard, all in one transaction. `version`
+-- is that of the stats the game was applied to (0 if the user had none yet). If they've changed
+-- since, nothing is recorded and false is returned, so that the game can be applied to the new
+-- stats instead. A game that's already been recorded fails with a unique violation.
+create or replace function record_game(
+  user_id uuid,
+  nonce numeric,
+  signature text,
+  pack text,
+  started_at timestamptz,
+  answers jsonb,
+  stats jsonb,
+  xp integer,
+  level integer,
+  version bigint
+)
+returns boolean
+language plpgsql
+set search_path = ''
+as $$
+#variable_conflict use_column
+begin
+  if record_game.version = 0 then
+    insert into public.verified_stats(user_id, data, version)
+    values (record_game.user_id, record_game.stats, 1)
+    on conflict (user_id) do nothing;
+  else
+    -- locks the row, so that a concurrent write waits and then finds the version has changed
+    update public.verified_stats
+    set data = record_game.stats, updated_at = now(), version = version + 1
+    where user_id = record_game.user_id and version = record_game.version;
+  end if;
+  if not found then
+    return false;
+  end if;
+
+  insert into public.recorded_games(user_id, nonce, signature, pack, started_at, answers)
+  values (
+    record_game.user_id,
+    record_game.nonce,
+    record_game.signature,
+    record_game.pack,
+    record_game.started_at,
+    record_game.answers
+  );
+
+  insert into public.leaderboard(user_id, xp, level, updated_at)
+  values (record_game.user_id, record_game.xp, record_game.level, now())
+  on conflict (user_id)
+  do update set xp = record_game.xp, level = record_game.level, updated_at = now();
+
+  return true;
+end;
+$$;
+
+-- Only the app server may record games, with the service role.
+revoke execute on function record_game from public, anon, authenticated;